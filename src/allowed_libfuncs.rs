@@ -0,0 +1,162 @@
+//! Validation of the libfuncs used by a contract against allowed libfunc lists.
+
+use crate::commands::compile_contract;
+use crate::commands::compile_raw::function_statement_ranges;
+use crate::error_kind::ErrorKind;
+use crate::json;
+use anyhow::{Context, Result};
+use cairo_lang_sierra::program::Statement;
+use cairo_lang_starknet_classes::allowed_libfuncs::{lookup_allowed_libfuncs_list, ListSelector};
+use cairo_lang_starknet_classes::contract_class::ContractClass;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Names of the allowed libfunc lists bundled with the compiler.
+pub const BUNDLED_LISTS: [&str; 3] = ["audited", "experimental", "all"];
+
+/// Bundled list the contracts are checked against when no other list is requested. It is the list
+/// of the newest bundled compiler, so older Sierra versions are only checked against explicit lists.
+pub const DEFAULT_LIST: &str = "audited";
+
+/// List of libfuncs a contract is allowed to use.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AllowedLibfuncsList {
+    /// One of the [`BUNDLED_LISTS`].
+    Name(String),
+    /// JSON file in the format of the bundled lists, i.e. `{"allowed_libfuncs": ["libfunc", ...]}`.
+    File(PathBuf),
+}
+
+impl fmt::Display for AllowedLibfuncsList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "`{name}` allowed libfuncs list"),
+            Self::File(path) => write!(f, "allowed libfuncs list {}", path.display()),
+        }
+    }
+}
+
+/// Returns the list to check the contract against: `allowed_libfuncs_list` if one was requested,
+/// otherwise the [`DEFAULT_LIST`] if the newest bundled compiler handles the contract's version.
+pub(crate) fn list_to_check(
+    sierra_json: &Value,
+    allowed_libfuncs_list: Option<AllowedLibfuncsList>,
+) -> Option<AllowedLibfuncsList> {
+    allowed_libfuncs_list.or_else(|| {
+        // Contracts without a readable version fail compilation with a better error.
+        compile_contract::is_compiled_by_latest_backend(sierra_json)
+            .unwrap_or(false)
+            .then(|| AllowedLibfuncsList::Name(DEFAULT_LIST.to_string()))
+    })
+}
+
+/// Libfunc used by a contract that is not on the allowed list.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct DisallowedLibfunc {
+    /// Generic libfunc, as it appears in the allowed lists, e.g. `felt252_add`.
+    pub libfunc: String,
+    /// Function that invokes the libfunc.
+    pub function: String,
+}
+
+impl fmt::Display for DisallowedLibfunc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in function {}", self.libfunc, self.function)
+    }
+}
+
+/// Returns every libfunc the contract uses that is not on `allowed_libfuncs_list`, along with the
+/// function it is used in. Each pair is reported once.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn find_disallowed_libfuncs(
+    sierra_json: &Value,
+    allowed_libfuncs_list: &AllowedLibfuncsList,
+) -> Result<Vec<DisallowedLibfunc>> {
+    let mut sierra_json = sierra_json.clone();
+    sierra_json["abi"] = Value::Null;
+    sierra_json["contract_class_version"] = Value::String(String::new());
+
+    // Sierra 0.1.0 keeps its version as a string in the first felt, which is read as major 0.
+    let sierra_version = compile_contract::parse_sierra_version(&sierra_json)?;
+    match sierra_version.first() {
+        Some(1) => {}
+        Some(0) => {
+            return Err(ErrorKind::UnsupportedVersion
+                .error("Validating allowed libfuncs is only supported for Sierra 1.0.0 and newer"))
+        }
+        _ => {
+            return Err(ErrorKind::UnsupportedVersion.error(format!(
+                "Validating allowed libfuncs is not supported for Sierra version {}",
+                compile_contract::format_sierra_version(&sierra_version)
+            )))
+        }
+    }
+
+    let list_selector = match allowed_libfuncs_list {
+        AllowedLibfuncsList::Name(name) => ListSelector::ListName(name.clone()),
+        AllowedLibfuncsList::File(path) => ListSelector::ListFile(path.display().to_string()),
+    };
+    let allowed_libfuncs = lookup_allowed_libfuncs_list(list_selector)
+        .with_context(|| format!("Unable to load {allowed_libfuncs_list}"))?
+        .allowed_libfuncs;
+
+    let sierra_class: ContractClass = json::from_value(sierra_json, "contract class")?;
+    let populate_debug_info = true;
+    let program = sierra_class
+        .extract_sierra_program(populate_debug_info)
+        .context(
+            ErrorKind::InvalidInput
+                .context("Unable to extract Sierra program from the contract class"),
+        )?;
+
+    let libfunc_declarations: HashMap<_, _> = program
+        .libfunc_declarations
+        .iter()
+        .map(|declaration| (&declaration.id, declaration))
+        .collect();
+    let mut disallowed_libfuncs = BTreeSet::new();
+    for (function, statements) in program
+        .funcs
+        .iter()
+        .zip(function_statement_ranges(&program))
+    {
+        for statement in &program.statements[statements] {
+            let Statement::Invocation(invocation) = statement else {
+                continue;
+            };
+            let declaration = libfunc_declarations
+                .get(&invocation.libfunc_id)
+                .context("Statement refers to an undeclared libfunc")?;
+            if !allowed_libfuncs.contains(&declaration.long_id.generic_id) {
+                disallowed_libfuncs.insert(DisallowedLibfunc {
+                    libfunc: declaration.long_id.generic_id.to_string(),
+                    function: function.id.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(disallowed_libfuncs.into_iter().collect())
+}
+
+/// Fails if the contract uses any libfunc that is not on `allowed_libfuncs_list`, listing them all.
+pub fn validate_allowed_libfuncs(
+    sierra_json: &Value,
+    allowed_libfuncs_list: &AllowedLibfuncsList,
+) -> Result<()> {
+    let disallowed_libfuncs = find_disallowed_libfuncs(sierra_json, allowed_libfuncs_list)?;
+    if disallowed_libfuncs.is_empty() {
+        return Ok(());
+    }
+
+    let disallowed_libfuncs = disallowed_libfuncs
+        .iter()
+        .map(|disallowed_libfunc| format!("  {disallowed_libfunc}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Err(ErrorKind::InvalidInput.error(format!(
+        "Contract uses libfuncs that are not on the {allowed_libfuncs_list}:\n{disallowed_libfuncs}"
+    )))
+}
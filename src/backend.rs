@@ -0,0 +1,595 @@
+//! Compilers of Sierra to CASM, each handling a range of Sierra versions.
+//!
+//! Supporting a new Sierra version means implementing [`SierraBackend`] for the compiler that
+//! handles it and registering it in [`BackendRegistry::with_bundled_backends`]. Downstream crates
+//! can register their own backends in a [`BackendRegistry`] of their own.
+//!
+//! The legacy backends are behind the default `sierra-0-1` and `sierra-1-0` cargo features, so
+//! builds that only compile current Sierra don't need their compilers. The latest backend is always
+//! built, as the analyses of compiled contracts and `compile-raw` rely on it.
+
+use crate::commands::compile_contract::format_sierra_version;
+use crate::commands::compile_raw::{self, CompileRawOptions};
+use crate::error_kind::ErrorKind;
+use crate::failing_statement::with_failing_statement;
+use crate::json;
+use anyhow::{Context, Result};
+use cairo_lang_sierra::debug_info::DebugInfo;
+#[cfg(any(feature = "sierra-0-1", feature = "sierra-1-0"))]
+use cairo_lang_sierra::extensions::gas::CostTokenType;
+use cairo_lang_sierra::program::Program;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use cairo_lang_starknet_classes::compiler_version::{current_compiler_version_id, VersionId};
+use cairo_lang_starknet_classes::contract_class::ContractClass;
+use cairo_lang_starknet_classes::felt252_serde::sierra_to_felt252s;
+#[cfg(feature = "sierra-0-1")]
+use cairo_lang_starknet_sierra_0_1_0::casm_contract_class::CasmContractClass as CasmContractClassSierraV0;
+#[cfg(feature = "sierra-0-1")]
+use cairo_lang_starknet_sierra_0_1_0::contract_class::ContractClass as ContractClassSierraV0;
+#[cfg(feature = "sierra-1-0")]
+use cairo_lang_starknet_sierra_1_0_0::casm_contract_class::CasmContractClass as CasmContractClassSierraV1;
+#[cfg(feature = "sierra-1-0")]
+use cairo_lang_starknet_sierra_1_0_0::compiler_version::{
+    current_compiler_version_id as current_compiler_version_id_sierra_v1,
+    VersionId as VersionIdSierraV1,
+};
+#[cfg(feature = "sierra-1-0")]
+use cairo_lang_starknet_sierra_1_0_0::contract_class::ContractClass as ContractClassSierraV1;
+#[cfg(feature = "sierra-1-0")]
+use cairo_lang_starknet_sierra_1_0_0::felt252_serde::sierra_to_felt252s as sierra_to_felt252s_sierra_v1;
+#[cfg(any(feature = "sierra-0-1", feature = "sierra-1-0"))]
+use serde_json::Map;
+use serde_json::{json, Value};
+use std::any::Any;
+use std::cell::Cell;
+#[cfg(any(feature = "sierra-0-1", feature = "sierra-1-0"))]
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Compiler of Sierra to CASM for a range of Sierra versions.
+pub trait SierraBackend: Send + Sync {
+    /// Name of the crate the backend compiles with.
+    fn name(&self) -> &str;
+
+    /// Version of the `cairo-lang` compiler the backend compiles with.
+    fn cairo_lang_version(&self) -> &str;
+
+    /// Sierra versions the backend handles, as `(major, minor)`. Any patch version of a supported
+    /// minor version is supported.
+    fn supported_sierra_versions(&self) -> RangeInclusive<(u8, u8)>;
+
+    /// Compiles Sierra of the Starknet contract to a serialized `CasmContractClass`.
+    fn compile_contract(&self, sierra_json: Value) -> Result<Value>;
+
+    /// Compiles Sierra of the plain Cairo code, serialized `cairo_lang_sierra::program::Program` or
+    /// `VersionedProgram`, to the output of `compile-raw`.
+    fn compile_raw(&self, _sierra_program: Value, _options: &CompileRawOptions) -> Result<Value> {
+        Err(ErrorKind::UnsupportedVersion.error(format!(
+            "Compiling Sierra programs is not supported by {} {}",
+            self.name(),
+            self.cairo_lang_version()
+        )))
+    }
+
+    /// Extracts the Sierra program from the felt252 encoded `sierra_program` of the Starknet
+    /// contract, with debug names populated from its debug info.
+    fn extract_program(&self, _sierra_json: Value) -> Result<ExtractedProgram> {
+        Err(ErrorKind::UnsupportedVersion.error(format!(
+            "Extracting Sierra programs is not supported by {} {}",
+            self.name(),
+            self.cairo_lang_version()
+        )))
+    }
+
+    /// Encodes the Sierra program, serialized `cairo_lang_sierra::program::Program`, as the
+    /// `sierra_program` and `sierra_program_debug_info` fields of a contract class, declaring it
+    /// as `sierra_version`, e.g. `[1, 0, 0]`, which is one of the supported versions.
+    fn encode_program(&self, _sierra_program: Value, _sierra_version: &[u8]) -> Result<Value> {
+        Err(ErrorKind::UnsupportedVersion.error(format!(
+            "Encoding Sierra programs is not supported by {} {}",
+            self.name(),
+            self.cairo_lang_version()
+        )))
+    }
+}
+
+/// Sierra program extracted from a contract class by [`SierraBackend::extract_program`].
+pub struct ExtractedProgram {
+    /// Serialized `cairo_lang_sierra::program::Program` of the backend's compiler.
+    pub program: Value,
+    /// The program in the textual Sierra form.
+    pub text: String,
+}
+
+/// Set of backends compilation is dispatched to by Sierra version.
+pub struct BackendRegistry {
+    backends: Vec<Box<dyn SierraBackend>>,
+}
+
+impl BackendRegistry {
+    /// Creates a registry without any backends.
+    pub fn new() -> Self {
+        Self { backends: vec![] }
+    }
+
+    /// Creates a registry with the backends bundled with USC, as enabled by cargo features.
+    pub fn with_bundled_backends() -> Self {
+        let mut registry = Self::new();
+        #[cfg(feature = "sierra-0-1")]
+        registry.register(SierraV0Backend);
+        #[cfg(feature = "sierra-1-0")]
+        registry.register(SierraV1Backend);
+        registry.register(LatestBackend);
+        registry
+    }
+
+    /// Adds the backend to the registry. It takes precedence over backends registered before it
+    /// for the Sierra versions they both support.
+    pub fn register(&mut self, backend: impl SierraBackend + 'static) {
+        self.backends.push(Box::new(backend));
+    }
+
+    pub fn backends(&self) -> impl Iterator<Item = &dyn SierraBackend> {
+        self.backends.iter().map(Box::as_ref)
+    }
+
+    /// Returns the backend supporting the Sierra version, if there is one.
+    pub fn find(&self, sierra_version: &[u8]) -> Option<&dyn SierraBackend> {
+        let &[major, minor, ..] = sierra_version else {
+            return None;
+        };
+
+        self.backends().rev().find(|backend| {
+            backend
+                .supported_sierra_versions()
+                .contains(&(major, minor))
+        })
+    }
+
+    /// Returns the backend supporting the newest Sierra version.
+    pub fn newest(&self) -> Option<&dyn SierraBackend> {
+        // `max_by_key` returns the last of equal elements, i.e. the one registered last.
+        self.backends()
+            .max_by_key(|backend| *backend.supported_sierra_versions().end())
+    }
+
+    /// Returns the newest Sierra version, as `(major, minor)`, supported by any backend.
+    pub fn max_supported_sierra_version(&self) -> Option<(u8, u8)> {
+        self.newest()
+            .map(|backend| *backend.supported_sierra_versions().end())
+    }
+}
+
+/// The default registry has the bundled backends, unlike [`BackendRegistry::new`].
+impl Default for BackendRegistry {
+    fn default() -> Self {
+        Self::with_bundled_backends()
+    }
+}
+
+/// Panic of a backend, caught so that it fails the compilation rather than the whole process.
+#[derive(Debug)]
+pub struct BackendPanic {
+    /// Name of the backend, as returned by [`SierraBackend::name`].
+    pub backend: String,
+    /// Version of `cairo-lang` the backend is built on.
+    pub cairo_lang_version: String,
+    /// Sierra version of the input, e.g. `1.0.0`, unless it is a program that doesn't carry one.
+    pub sierra_version: Option<String>,
+    /// Message the backend panicked with.
+    pub message: String,
+}
+
+impl fmt::Display for BackendPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} panicked", self.backend, self.cairo_lang_version)?;
+        if let Some(sierra_version) = &self.sierra_version {
+            write!(f, " on Sierra version {sierra_version}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for BackendPanic {}
+
+thread_local! {
+    /// Whether a panic on this thread is caught as a [`BackendPanic`].
+    static CATCHING_BACKEND_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Replaces the panic hook with one that stays silent about the panics caught as [`BackendPanic`]
+/// errors, and passes the other panics to the hook it replaces. Without it, the caught panics are
+/// reported by the hook as well.
+pub fn install_quiet_panic_hook() {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !CATCHING_BACKEND_PANIC.try_with(Cell::get).unwrap_or(false) {
+            previous_hook(info);
+        }
+    }));
+}
+
+/// Calls `invoke` with the backend chosen for the Sierra version, turning its panics into
+/// [`BackendPanic`] errors.
+pub(crate) fn catch_backend_panic<T>(
+    backend: &dyn SierraBackend,
+    sierra_version: &[u8],
+    invoke: impl FnOnce(&dyn SierraBackend) -> Result<T>,
+) -> Result<T> {
+    catch_panic(backend, Some(sierra_version), invoke)
+}
+
+/// Like [`catch_backend_panic`] with [`LatestBackend`], for programs that don't carry their Sierra
+/// version.
+pub(crate) fn catch_latest_backend_panic<T>(invoke: impl FnOnce() -> Result<T>) -> Result<T> {
+    catch_panic(&LatestBackend, None, |_| invoke())
+}
+
+fn catch_panic<T>(
+    backend: &dyn SierraBackend,
+    sierra_version: Option<&[u8]>,
+    invoke: impl FnOnce(&dyn SierraBackend) -> Result<T>,
+) -> Result<T> {
+    let was_catching = CATCHING_BACKEND_PANIC.replace(true);
+    // Backends keep no state between invocations, that a panic could leave inconsistent.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| invoke(backend)));
+    CATCHING_BACKEND_PANIC.set(was_catching);
+
+    result.unwrap_or_else(|payload| {
+        Err(BackendPanic {
+            backend: backend.name().to_string(),
+            cairo_lang_version: backend.cairo_lang_version().to_string(),
+            sierra_version: sierra_version.map(format_sierra_version),
+            message: panic_message(payload.as_ref()),
+        }
+        .into())
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Sierra versions handled by `SierraV0Backend`.
+const SIERRA_V0_VERSIONS: RangeInclusive<(u8, u8)> = (0, 0)..=(0, u8::MAX);
+
+/// Sierra versions handled by `SierraV1Backend`.
+const SIERRA_V1_VERSIONS: RangeInclusive<(u8, u8)> = (1, 0)..=(1, 1);
+
+/// Sierra versions handled by [`LatestBackend`].
+pub(crate) const LATEST_SIERRA_VERSIONS: RangeInclusive<(u8, u8)> = (1, 2)..=(1, 9);
+
+/// Bundled backends left out of the build, with the cargo features enabling them.
+const DISABLED_BACKENDS: &[(&str, RangeInclusive<(u8, u8)>)] = &[
+    #[cfg(not(feature = "sierra-0-1"))]
+    ("sierra-0-1", SIERRA_V0_VERSIONS),
+    #[cfg(not(feature = "sierra-1-0"))]
+    ("sierra-1-0", SIERRA_V1_VERSIONS),
+];
+
+/// Fails with the cargo feature to enable if the bundled backend of the Sierra version is not
+/// compiled in.
+pub(crate) fn ensure_backend_compiled_in(sierra_version: &[u8]) -> Result<()> {
+    if let Some(feature) = disabled_backend_feature(sierra_version) {
+        return Err(ErrorKind::UnsupportedVersion.error(format!(
+            "Unable to compile Sierra to Casm. The backend for Sierra version {} is not compiled in, \
+            rebuild USC with the `{feature}` feature enabled",
+            format_sierra_version(sierra_version)
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the cargo feature enabling the bundled backend of the Sierra version, if that backend
+/// is not compiled in.
+pub(crate) fn disabled_backend_feature(sierra_version: &[u8]) -> Option<&'static str> {
+    let &[major, minor, ..] = sierra_version else {
+        return None;
+    };
+
+    DISABLED_BACKENDS
+        .iter()
+        .find(|(_, versions)| versions.contains(&(major, minor)))
+        .map(|(feature, _)| *feature)
+}
+
+/// Compiles a Sierra program with a legacy `cairo-lang-sierra-to-casm`, producing the same output
+/// as the latest compiler apart from the fields those compilers can't provide.
+#[cfg(any(feature = "sierra-0-1", feature = "sierra-1-0"))]
+macro_rules! compile_legacy_raw {
+    ($sierra:ident, $sierra_to_casm:ident, $sierra_program:expr, $options:expr) => {{
+        if $options.statement_metadata {
+            return Err(ErrorKind::Usage.error(
+                "Statement metadata is only supported for Sierra 1.2.0 and newer",
+            ));
+        }
+        let sierra_program: $sierra::program::Program =
+            json::from_value($sierra_program, "Sierra program")?;
+
+        let metadata = $sierra_to_casm::metadata::calc_metadata(
+            &sierra_program,
+            $sierra_to_casm::metadata::MetadataComputationConfig::default(),
+        )
+        .map_err(|error| anyhow::anyhow!("{error}"))?;
+        let gas_usage_check = true;
+        let cairo_program =
+            $sierra_to_casm::compiler::compile(&sierra_program, &metadata, gas_usage_check)
+                .map_err(|error| anyhow::anyhow!("{error}"))?;
+
+        let mut bytecode = vec![];
+        let mut hints = vec![];
+        let mut instruction_offsets = vec![];
+        for instruction in &cairo_program.instructions {
+            instruction_offsets.push(bytecode.len());
+            if !instruction.hints.is_empty() {
+                hints.push((bytecode.len(), instruction.hints.clone()));
+            }
+            bytecode.extend(instruction.assemble().encode());
+        }
+        // Legacy compilers only record the offset of each statement, the index of the instruction
+        // starting there is recovered from the offsets.
+        let debug_info: Vec<(usize, usize)> = cairo_program
+            .debug_info
+            .sierra_statement_info
+            .iter()
+            .map(|statement_info| {
+                let offset = statement_info.code_offset;
+                let instruction_idx = instruction_offsets
+                    .partition_point(|&instruction_offset| instruction_offset < offset);
+                (offset, instruction_idx)
+            })
+            .collect();
+        let function_costs: HashMap<usize, Map<String, Value>> = sierra_program
+            .funcs
+            .iter()
+            .map(|function| {
+                let costs = metadata.gas_info.function_costs[&function.id]
+                    .iter()
+                    .map(|(token_type, value)| {
+                        let token_type = latest_cost_token_type!($sierra, token_type);
+                        (token_type.name(), Value::from(*value))
+                    })
+                    .collect();
+                (function.entry_point.0, costs)
+            })
+            .collect();
+
+        Ok(json!({
+            "assembled_cairo_program": {
+                "bytecode": serde_json::to_value(bytecode)?,
+                "hints": serde_json::to_value(hints)?
+            },
+            "debug_info": debug_info,
+            "function_costs": function_costs,
+        }))
+    }};
+}
+
+/// Splits a Sierra version, e.g. `[1, 0, 0]`, into the parts of the compilers' `VersionId`.
+fn version_id_parts(sierra_version: &[u8]) -> (usize, usize, usize) {
+    let part = |idx: usize| sierra_version.get(idx).copied().map_or(0, usize::from);
+    (part(0), part(1), part(2))
+}
+
+/// Converts a `CostTokenType` of a legacy `cairo-lang-sierra` to the latest one, whose name costs
+/// are reported under.
+#[cfg(any(feature = "sierra-0-1", feature = "sierra-1-0"))]
+macro_rules! latest_cost_token_type {
+    ($sierra:ident, $token_type:expr) => {{
+        use $sierra::extensions::gas::CostTokenType as LegacyCostTokenType;
+        match $token_type {
+            LegacyCostTokenType::Const => CostTokenType::Const,
+            LegacyCostTokenType::Step => CostTokenType::Step,
+            LegacyCostTokenType::Hole => CostTokenType::Hole,
+            LegacyCostTokenType::RangeCheck => CostTokenType::RangeCheck,
+            LegacyCostTokenType::Pedersen => CostTokenType::Pedersen,
+            LegacyCostTokenType::Poseidon => CostTokenType::Poseidon,
+            LegacyCostTokenType::Bitwise => CostTokenType::Bitwise,
+            LegacyCostTokenType::EcOp => CostTokenType::EcOp,
+        }
+    }};
+}
+
+/// Backend of Sierra 0.1.0, built on `cairo-lang-starknet` v1.0.0-alpha.6.
+#[cfg(feature = "sierra-0-1")]
+pub struct SierraV0Backend;
+
+#[cfg(feature = "sierra-0-1")]
+impl SierraBackend for SierraV0Backend {
+    fn name(&self) -> &str {
+        "cairo-lang-starknet"
+    }
+
+    fn cairo_lang_version(&self) -> &str {
+        "1.0.0-alpha.6"
+    }
+
+    fn supported_sierra_versions(&self) -> RangeInclusive<(u8, u8)> {
+        SIERRA_V0_VERSIONS
+    }
+
+    fn compile_contract(&self, sierra_json: Value) -> Result<Value> {
+        let sierra_class: ContractClassSierraV0 = json::from_value(sierra_json, "contract class")?;
+        let casm_class = CasmContractClassSierraV0::from_contract_class(sierra_class, true)
+            .map_err(|error| anyhow::anyhow!("{error}"))?;
+        Ok(serde_json::to_value(&casm_class)?)
+    }
+
+    fn extract_program(&self, sierra_json: Value) -> Result<ExtractedProgram> {
+        let sierra_class: ContractClassSierraV0 = json::from_value(sierra_json, "contract class")?;
+        let program = sierra_class.extract_sierra_program().map_err(|error| {
+            ErrorKind::InvalidInput.error(format!(
+                "Unable to extract Sierra program from the contract class: {error:?}"
+            ))
+        })?;
+        Ok(ExtractedProgram {
+            program: serde_json::to_value(&program)?,
+            text: program.to_string(),
+        })
+    }
+
+    /// Sierra 0.1.0 felts start with a fixed `0.1.0` string rather than the version ids the other
+    /// backends encode, so encoding is left to them.
+    fn encode_program(&self, _sierra_program: Value, _sierra_version: &[u8]) -> Result<Value> {
+        Err(ErrorKind::UnsupportedVersion.error(
+            "Encoding Sierra programs as felts is only supported for Sierra 1.0.0 and newer",
+        ))
+    }
+
+    fn compile_raw(&self, sierra_program: Value, options: &CompileRawOptions) -> Result<Value> {
+        compile_legacy_raw!(
+            cairo_lang_sierra_sierra_0_1_0,
+            cairo_lang_sierra_to_casm_sierra_0_1_0,
+            sierra_program,
+            options
+        )
+    }
+}
+
+/// Backend of Sierra 1.0.0 and 1.1.0, built on `cairo-lang-starknet` v1.0.0-rc0 through the USC
+/// proxy crate.
+#[cfg(feature = "sierra-1-0")]
+pub struct SierraV1Backend;
+
+#[cfg(feature = "sierra-1-0")]
+impl SierraBackend for SierraV1Backend {
+    fn name(&self) -> &str {
+        "universal-sierra-compiler-cairo-lang-starknet-proxy"
+    }
+
+    fn cairo_lang_version(&self) -> &str {
+        "1.0.0-rc0"
+    }
+
+    fn supported_sierra_versions(&self) -> RangeInclusive<(u8, u8)> {
+        SIERRA_V1_VERSIONS
+    }
+
+    fn compile_contract(&self, sierra_json: Value) -> Result<Value> {
+        let sierra_class: ContractClassSierraV1 = json::from_value(sierra_json, "contract class")?;
+        let casm_class = CasmContractClassSierraV1::from_contract_class(sierra_class, true)
+            .map_err(|error| anyhow::anyhow!("{error}"))?;
+        Ok(serde_json::to_value(&casm_class)?)
+    }
+
+    fn extract_program(&self, sierra_json: Value) -> Result<ExtractedProgram> {
+        let sierra_class: ContractClassSierraV1 = json::from_value(sierra_json, "contract class")?;
+        let program = sierra_class.extract_sierra_program().map_err(|error| {
+            ErrorKind::InvalidInput.error(format!(
+                "Unable to extract Sierra program from the contract class: {error:?}"
+            ))
+        })?;
+        Ok(ExtractedProgram {
+            program: serde_json::to_value(&program)?,
+            text: program.to_string(),
+        })
+    }
+
+    fn encode_program(&self, sierra_program: Value, sierra_version: &[u8]) -> Result<Value> {
+        let program: cairo_lang_sierra_sierra_1_0_0::program::Program =
+            json::from_value(sierra_program, "Sierra program")?;
+        let (major, minor, patch) = version_id_parts(sierra_version);
+        let sierra_version_id = VersionIdSierraV1 {
+            major,
+            minor,
+            patch,
+        };
+        let sierra_program = sierra_to_felt252s_sierra_v1(
+            sierra_version_id,
+            current_compiler_version_id_sierra_v1(),
+            &program,
+        )
+        .map_err(|error| anyhow::anyhow!("Unable to encode Sierra program: {error:?}"))?;
+        Ok(json!({
+            "sierra_program": sierra_program,
+            "sierra_program_debug_info": cairo_lang_sierra_sierra_1_0_0::debug_info::DebugInfo::extract(&program),
+        }))
+    }
+
+    fn compile_raw(&self, sierra_program: Value, options: &CompileRawOptions) -> Result<Value> {
+        compile_legacy_raw!(
+            cairo_lang_sierra_sierra_1_0_0,
+            cairo_lang_sierra_to_casm_sierra_1_0_0,
+            sierra_program,
+            options
+        )
+    }
+}
+
+/// Extracts the Sierra program of a contract class of the latest backend, with its debug info.
+pub(crate) fn extract_latest_program(sierra_class: &ContractClass) -> Result<Program> {
+    let populate_debug_info = true;
+    sierra_class
+        .extract_sierra_program(populate_debug_info)
+        .context(
+            ErrorKind::InvalidInput
+                .context("Unable to extract Sierra program from the contract class"),
+        )
+}
+
+/// Backend of Sierra 1.2.0 and newer, built on the latest `cairo-lang-starknet-classes`.
+pub struct LatestBackend;
+
+impl SierraBackend for LatestBackend {
+    fn name(&self) -> &str {
+        "cairo-lang-starknet-classes"
+    }
+
+    fn cairo_lang_version(&self) -> &str {
+        // Keep in sync with the version of `cairo-lang-starknet-classes` in Cargo.toml.
+        "2.19.3"
+    }
+
+    fn supported_sierra_versions(&self) -> RangeInclusive<(u8, u8)> {
+        LATEST_SIERRA_VERSIONS
+    }
+
+    fn compile_contract(&self, sierra_json: Value) -> Result<Value> {
+        let sierra_class: ContractClass = json::from_value(sierra_json, "contract class")?;
+        let program = extract_latest_program(&sierra_class)?;
+        // The compiler takes the program, which is kept to point at the statement it fails at.
+        let casm_class =
+            CasmContractClass::from_contract_class(sierra_class, program.clone(), true, usize::MAX)
+                .map_err(|error| with_failing_statement(error, &program))?;
+        Ok(serde_json::to_value(casm_class)?)
+    }
+
+    fn extract_program(&self, sierra_json: Value) -> Result<ExtractedProgram> {
+        let sierra_class: ContractClass = json::from_value(sierra_json, "contract class")?;
+        let program = extract_latest_program(&sierra_class)?;
+        Ok(ExtractedProgram {
+            program: serde_json::to_value(&program)?,
+            text: program.to_string(),
+        })
+    }
+
+    fn encode_program(&self, sierra_program: Value, sierra_version: &[u8]) -> Result<Value> {
+        let program = compile_raw::deserialize_program(sierra_program)?;
+        let (major, minor, patch) = version_id_parts(sierra_version);
+        let sierra_version_id = VersionId {
+            major,
+            minor,
+            patch,
+        };
+        let sierra_program =
+            sierra_to_felt252s(sierra_version_id, current_compiler_version_id(), &program)
+                .context("Unable to encode Sierra program")?;
+        Ok(json!({
+            "sierra_program": sierra_program,
+            "sierra_program_debug_info": DebugInfo::extract(&program),
+        }))
+    }
+
+    fn compile_raw(&self, sierra_program: Value, options: &CompileRawOptions) -> Result<Value> {
+        let sierra_program = compile_raw::deserialize_program(sierra_program)?;
+        compile_raw::compile_uncaught(&sierra_program, options)
+    }
+}
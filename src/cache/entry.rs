@@ -2,7 +2,7 @@
 //!
 //! An entry owns both the location of `casm.json` and the fingerprint its contents must match.
 
-use super::SierraKind;
+use crate::commands::SierraKind;
 use anyhow::{Context, Result};
 use scarb_stable_hash::StableHasher;
 use serde_json::Value;
@@ -30,6 +30,7 @@ impl CasmCacheEntry {
         sierra_path: &Path,
         sierra_content: &[u8],
         sierra_kind: SierraKind,
+        variant: &str,
     ) -> Result<Self> {
         let fingerprint = short_hash(sierra_content);
         let canonical_sierra_path = fs::canonicalize(sierra_path).with_context(|| {
@@ -38,7 +39,16 @@ impl CasmCacheEntry {
                 sierra_path.display()
             )
         })?;
-        let slot_id = short_hash(canonical_sierra_path.as_os_str().as_encoded_bytes());
+        // Outputs compiled with different options get separate slots, so they don't evict each other.
+        let mut slot_key = canonical_sierra_path
+            .as_os_str()
+            .as_encoded_bytes()
+            .to_vec();
+        if !variant.is_empty() {
+            slot_key.push(0);
+            slot_key.extend_from_slice(variant.as_bytes());
+        }
+        let slot_id = short_hash(&slot_key);
         let path = cache_dir
             .join(CASM_CACHE_DIR)
             .join(USC_VERSION)
@@ -190,7 +200,7 @@ mod tests {
 
     fn entry(cache_dir: &Path, source_path: &Path) -> CasmCacheEntry {
         let source_content = fs::read(source_path).unwrap();
-        CasmCacheEntry::new(cache_dir, source_path, &source_content, SierraKind::Raw).unwrap()
+        CasmCacheEntry::new(cache_dir, source_path, &source_content, SierraKind::Raw, "").unwrap()
     }
 
     #[test]
@@ -270,6 +280,42 @@ mod tests {
         assert_ne!(short_hash(&big), short_hash(&mutated));
     }
 
+    #[test]
+    fn separates_slots_by_variant() {
+        let temp = tempfile::tempdir().unwrap();
+        let source_path = write_file(temp.path(), "program.sierra.json", b"{}");
+        let source_content = fs::read(&source_path).unwrap();
+        let new_entry = |variant| {
+            CasmCacheEntry::new(
+                temp.path(),
+                &source_path,
+                &source_content,
+                SierraKind::Raw,
+                variant,
+            )
+            .unwrap()
+        };
+
+        let default_entry = new_entry("");
+        let variant_entry = new_entry("statement-metadata");
+        default_entry
+            .store(&json!({"compiled": "default"}))
+            .unwrap();
+        variant_entry
+            .store(&json!({"compiled": "variant"}))
+            .unwrap();
+
+        assert_ne!(default_entry.casm_path(), variant_entry.casm_path());
+        assert_eq!(
+            default_entry.load().unwrap(),
+            json!({"compiled": "default"})
+        );
+        assert_eq!(
+            variant_entry.load().unwrap(),
+            json!({"compiled": "variant"})
+        );
+    }
+
     #[test]
     fn uses_expected_layout() {
         let temp = tempfile::tempdir().unwrap();
@@ -279,7 +325,7 @@ mod tests {
         for (kind, kind_dir) in [(SierraKind::Raw, "raw"), (SierraKind::Contract, "contract")] {
             let source_content = fs::read(&source_path).unwrap();
             let entry =
-                CasmCacheEntry::new(&cache_dir, &source_path, &source_content, kind).unwrap();
+                CasmCacheEntry::new(&cache_dir, &source_path, &source_content, kind, "").unwrap();
             let relative_path = entry.casm_path().strip_prefix(&cache_dir).unwrap();
             let components: Vec<_> = relative_path.components().collect();
 
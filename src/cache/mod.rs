@@ -1,5 +1,7 @@
 //! Optional persistent cache for CASM compiled from Sierra.
 
+use crate::commands::SierraKind;
+use crate::messages::{self, CacheEvent};
 use anyhow::{Context, Result};
 use entry::CasmCacheEntry;
 use serde_json::Value;
@@ -7,12 +9,6 @@ use std::path::Path;
 
 mod entry;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum SierraKind {
-    Raw,
-    Contract,
-}
-
 impl SierraKind {
     fn as_str(self) -> &'static str {
         match self {
@@ -24,11 +20,25 @@ impl SierraKind {
 
 /// Returns the CASM for `sierra_path`, serving it from `cache_dir` when a valid entry exists.
 /// With no `cache_dir` provided or a cache miss, the `compile` closure is called.
+/// The CASM is cached as compiled with the default options, see [`compile_variant_with_cache`].
 pub fn compile_with_cache(
     sierra_path: &Path,
     sierra_kind: SierraKind,
     cache_dir: Option<&Path>,
     compile: impl FnOnce(&[u8]) -> Result<Value>,
+) -> Result<Value> {
+    compile_variant_with_cache(sierra_path, sierra_kind, "", cache_dir, compile)
+}
+
+/// Like [`compile_with_cache`], for CASM compiled with other than the default options.
+/// `variant` identifies the compilation options (empty for the defaults), so outputs compiled with
+/// different options are cached separately.
+pub fn compile_variant_with_cache(
+    sierra_path: &Path,
+    sierra_kind: SierraKind,
+    variant: &str,
+    cache_dir: Option<&Path>,
+    compile: impl FnOnce(&[u8]) -> Result<Value>,
 ) -> Result<Value> {
     let sierra_content = std::fs::read(sierra_path).with_context(|| {
         format!(
@@ -41,6 +51,7 @@ pub fn compile_with_cache(
         sierra_path,
         &sierra_content,
         sierra_kind,
+        variant,
         cache_dir,
         compile,
     )
@@ -50,6 +61,7 @@ fn compile_content_with_cache(
     sierra_path: &Path,
     sierra_content: &[u8],
     sierra_kind: SierraKind,
+    variant: &str,
     cache_dir: Option<&Path>,
     compile: impl FnOnce(&[u8]) -> Result<Value>,
 ) -> Result<Value> {
@@ -57,20 +69,23 @@ fn compile_content_with_cache(
         return compile(sierra_content);
     };
 
-    let entry = match CasmCacheEntry::new(cache_dir, sierra_path, sierra_content, sierra_kind) {
-        Ok(entry) => entry,
-        Err(error) => {
-            tracing::debug!(
-                path = %sierra_path.display(),
-                %error,
-                "failed to initialize CASM cache entry"
-            );
-            return compile(sierra_content);
-        }
-    };
+    let entry =
+        match CasmCacheEntry::new(cache_dir, sierra_path, sierra_content, sierra_kind, variant) {
+            Ok(entry) => entry,
+            Err(error) => {
+                tracing::debug!(
+                    path = %sierra_path.display(),
+                    %error,
+                    "failed to initialize CASM cache entry"
+                );
+                return compile(sierra_content);
+            }
+        };
     if let Some(output) = entry.load() {
+        messages::cache_event(CacheEvent::Hit, sierra_path);
         return Ok(output);
     }
+    messages::cache_event(CacheEvent::Miss, sierra_path);
 
     let output = compile(sierra_content)?;
 
@@ -161,6 +176,37 @@ mod tests {
         assert_eq!(count_files_named(temp.path(), "casm.json"), 2);
     }
 
+    #[test]
+    fn separates_entries_by_variant() {
+        let temp = tempfile::tempdir().unwrap();
+        let source_path = write_source(
+            temp.path(),
+            "program.sierra.json",
+            &json!({"program": "same"}),
+        );
+        let default = compile_with_cache(&source_path, SierraKind::Raw, Some(temp.path()), |_| {
+            Ok(json!({"compiled": "default"}))
+        })
+        .unwrap();
+        let variant = compile_variant_with_cache(
+            &source_path,
+            SierraKind::Raw,
+            "statement-metadata",
+            Some(temp.path()),
+            |_| Ok(json!({"compiled": "variant"})),
+        )
+        .unwrap();
+        let cached_default =
+            compile_with_cache(&source_path, SierraKind::Raw, Some(temp.path()), |_| {
+                panic!("default cache entry should survive caching another variant")
+            })
+            .unwrap();
+
+        assert_ne!(default, variant);
+        assert_eq!(cached_default, default);
+        assert_eq!(count_files_named(temp.path(), "casm.json"), 2);
+    }
+
     #[test]
     fn changed_input_replaces_entry() {
         let temp = tempfile::tempdir().unwrap();
@@ -239,6 +285,7 @@ mod tests {
             &missing_source_path,
             b"{}",
             SierraKind::Raw,
+            "",
             Some(temp.path()),
             |_| Ok(json!({"compiled": 5})),
         )
@@ -0,0 +1,131 @@
+//! Human-readable CASM listing of compiled Sierra.
+
+use crate::backend::catch_latest_backend_panic;
+use crate::commands::compile_contract::{self, CompiledContractProgram};
+use crate::commands::compile_raw::{self, CompiledProgram};
+use anyhow::Result;
+use cairo_lang_sierra::debug_info::DebugInfo;
+use cairo_lang_sierra::program::Program;
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Compiles Sierra of the plain Cairo code and renders the CASM in Cairo assembly syntax.
+/// Every block of instructions is preceded by the Sierra statement (and function) it came from.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile_raw(sierra_program: &Program) -> Result<String> {
+    let compiled = catch_latest_backend_panic(|| {
+        compile_raw::compile_program(sierra_program, MetadataComputationConfig::default())
+    })?;
+
+    Ok(render(sierra_program, &compiled, &HashMap::new()))
+}
+
+/// Compiles Sierra of the Starknet contract and renders the CASM in Cairo assembly syntax.
+/// On top of the Sierra statement comments, the listing marks where each entry point starts.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile_contract(sierra_json: Value) -> Result<String> {
+    Ok(from_compiled(&compile_contract::compile_program(
+        sierra_json,
+    )?))
+}
+
+/// Renders the CASM of the already compiled contract.
+pub(crate) fn from_compiled(compiled_contract: &CompiledContractProgram) -> String {
+    let CompiledContractProgram {
+        sierra_class,
+        program,
+        compiled,
+        ..
+    } = compiled_contract;
+
+    let entry_points = &sierra_class.entry_points_by_type;
+    let mut labels: HashMap<usize, Vec<String>> = HashMap::new();
+    for (entry_point_type, entry_points) in [
+        ("CONSTRUCTOR", &entry_points.constructor),
+        ("EXTERNAL", &entry_points.external),
+        ("L1_HANDLER", &entry_points.l1_handler),
+    ] {
+        for entry_point in entry_points {
+            let statement_idx = program.funcs[entry_point.function_idx].entry_point.0;
+            labels.entry(statement_idx).or_default().push(format!(
+                "{entry_point_type} entry point, selector {:#x}",
+                entry_point.selector
+            ));
+        }
+    }
+
+    render(program, compiled, &labels)
+}
+
+fn render(
+    sierra_program: &Program,
+    compiled: &CompiledProgram,
+    labels: &HashMap<usize, Vec<String>>,
+) -> String {
+    let sierra_program = with_libfunc_names(sierra_program);
+    let functions: HashMap<usize, &_> = sierra_program
+        .funcs
+        .iter()
+        .map(|function| (function.entry_point.0, function))
+        .collect();
+    let instructions = &compiled.cairo_program.instructions;
+    let statements_info = &compiled.cairo_program.debug_info.sierra_statement_info;
+
+    let mut listing = String::new();
+    for (statement_idx, statement_info) in statements_info.iter().enumerate() {
+        if let Some(function) = functions.get(&statement_idx) {
+            if !listing.is_empty() {
+                listing.push('\n');
+            }
+            writeln!(listing, "// Function {}", function.id).unwrap();
+        }
+        for label in labels.get(&statement_idx).into_iter().flatten() {
+            writeln!(listing, "// {label}").unwrap();
+        }
+        if let Some(statement) = sierra_program.statements.get(statement_idx) {
+            writeln!(listing, "// #{statement_idx}: {statement}").unwrap();
+        }
+
+        let end_idx = statements_info
+            .get(statement_idx + 1)
+            .map_or(instructions.len(), |next| next.instruction_idx);
+        let mut pc = statement_info.start_offset;
+        for instruction in &instructions[statement_info.instruction_idx..end_idx] {
+            // Hints are rendered on separate lines preceding the instruction body.
+            let text = instruction.to_string();
+            let mut lines: Vec<&str> = text.lines().collect();
+            let body = lines.pop().unwrap_or_default();
+            for hint in lines {
+                writeln!(listing, "{:>8}  {hint}", "").unwrap();
+            }
+            writeln!(listing, "{pc:>8}: {body};").unwrap();
+            pc += instruction.body.op_size();
+        }
+    }
+
+    listing
+}
+
+/// Returns a copy of the program where libfuncs without a debug name are named after their long
+/// id (e.g. `store_temp<[12]>`), so the listing shows which libfunc every statement invokes.
+fn with_libfunc_names(sierra_program: &Program) -> Program {
+    let mut program = sierra_program.clone();
+    let debug_info = DebugInfo {
+        libfunc_names: program
+            .libfunc_declarations
+            .iter()
+            .filter(|declaration| declaration.id.debug_name.is_none())
+            .map(|declaration| {
+                (
+                    declaration.id.clone(),
+                    declaration.long_id.to_string().into(),
+                )
+            })
+            .collect(),
+        ..DebugInfo::default()
+    };
+    debug_info.populate(&mut program);
+    program
+}
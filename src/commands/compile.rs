@@ -0,0 +1,27 @@
+use crate::commands::Emit;
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args, Clone)]
+pub struct Compile {
+    /// Path to the sierra json file of the contract, or to the sierra program in json or textual
+    /// form. Which of them it is, is detected from its content
+    #[arg(short, long)]
+    pub sierra_path: PathBuf,
+
+    /// Path to where the compilation result will be saved
+    #[arg(short, long)]
+    pub output_path: Option<PathBuf>,
+
+    /// Directory where compiled CASM entries should be cached.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Format of the compilation result.
+    #[arg(long, value_enum, default_value_t)]
+    pub emit: Emit,
+
+    /// Keep running and recompile the input whenever it changes.
+    #[arg(long)]
+    pub watch: bool,
+}
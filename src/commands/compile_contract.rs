@@ -1,15 +1,30 @@
+use crate::allowed_libfuncs::{
+    self, validate_allowed_libfuncs, AllowedLibfuncsList, BUNDLED_LISTS, DEFAULT_LIST,
+};
+use crate::backend::{
+    catch_backend_panic, ensure_backend_compiled_in, extract_latest_program, BackendRegistry,
+    LatestBackend, LATEST_SIERRA_VERSIONS,
+};
+use crate::commands::compile_raw::{
+    compile_program as compile_raw_program, statement_pc_ranges, CompiledProgram,
+};
+use crate::commands::Emit;
+use crate::error_kind::ErrorKind;
+use crate::failing_statement::with_failing_statement;
+use crate::json;
 use anyhow::{Context, Result};
-use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
-use cairo_lang_starknet_classes::contract_class::ContractClass;
-use cairo_lang_starknet_sierra_0_1_0::casm_contract_class::CasmContractClass as CasmContractClassSierraV0;
-use cairo_lang_starknet_sierra_0_1_0::contract_class::ContractClass as ContractClassSierraV0;
-use cairo_lang_starknet_sierra_1_0_0::casm_contract_class::CasmContractClass as CasmContractClassSierraV1;
-use cairo_lang_starknet_sierra_1_0_0::contract_class::ContractClass as ContractClassSierraV1;
+use cairo_lang_sierra::extensions::gas::CostTokenType;
+use cairo_lang_sierra::program::Program;
+use cairo_lang_sierra_to_casm::metadata::{calc_metadata, Metadata, MetadataComputationConfig};
+use cairo_lang_sierra_type_size::ProgramRegistryInfo;
+use cairo_lang_starknet_classes::casm_contract_class::{CasmContractClass, ENTRY_POINT_COST};
+use cairo_lang_starknet_classes::contract_class::{ContractClass, ContractEntryPoints};
 use clap::Args;
 use serde_json::Value;
+use std::ops::Range;
 use std::path::PathBuf;
 
-#[derive(Args)]
+#[derive(Args, Clone)]
 pub struct CompileContract {
     /// Path to the sierra json file, which should have
     /// `sierra_program` and `entry_points_by_type` fields
@@ -24,59 +39,362 @@ pub struct CompileContract {
     /// Directory where compiled CASM entries should be cached.
     #[arg(long)]
     pub cache_dir: Option<PathBuf>,
+
+    /// Format of the compilation result.
+    #[arg(long, value_enum, default_value_t)]
+    pub emit: Emit,
+
+    /// Path to where debug info json file will be saved.
+    /// It maps every Sierra statement to its CASM PC range and every entry point to its function
+    #[arg(long)]
+    pub debug_info_path: Option<PathBuf>,
+
+    /// Path to where entry point costs json file will be saved.
+    /// It lists the offset, bytecode length and gas costs of every entry point's function
+    #[arg(long)]
+    pub entry_point_costs_path: Option<PathBuf>,
+
+    /// Fail if the contract uses libfuncs that are not on this bundled allowed libfuncs list
+    #[arg(long, value_parser = BUNDLED_LISTS, conflicts_with = "allowed_libfuncs_list_file")]
+    pub allowed_libfuncs_list_name: Option<String>,
+
+    /// Fail if the contract uses libfuncs that are not on the allowed libfuncs list in this json file.
+    /// It should have an `allowed_libfuncs` field with an array of libfunc names
+    #[arg(long)]
+    pub allowed_libfuncs_list_file: Option<PathBuf>,
+
+    /// Only warn about libfuncs that are not on the allowed libfuncs list instead of failing
+    #[arg(long)]
+    pub allow_disallowed_libfuncs: bool,
+
+    /// Don't check the libfuncs used by the contract, which are otherwise checked against the
+    /// `audited` list for the Sierra versions of the newest bundled compiler
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "allowed_libfuncs_list_name",
+            "allowed_libfuncs_list_file",
+            "allow_disallowed_libfuncs",
+        ]
+    )]
+    pub skip_allowed_libfuncs_check: bool,
+
+    /// Compile Sierra versions newer than the bundled compilers support with the newest of them,
+    /// instead of failing. The result may be incorrect
+    #[arg(long)]
+    pub allow_unsupported_version: bool,
+
+    /// Directory with other USC binaries. Contracts with Sierra versions the bundled compilers
+    /// don't support are compiled with the first of them that supports the version
+    #[arg(long)]
+    pub delegate_dir: Option<PathBuf>,
+
+    /// Keep running and recompile the contract whenever its Sierra changes.
+    #[arg(long)]
+    pub watch: bool,
+}
+
+/// Sierra program of a contract compiled the same way `CasmContractClass::from_contract_class`
+/// compiles it, keeping the intermediate results that `CasmContractClass` does not carry.
+pub struct CompiledContractProgram {
+    pub sierra_class: ContractClass,
+    pub program: Program,
+    pub compiled: CompiledProgram,
+    sierra_version: Vec<u8>,
+}
+
+impl CompiledContractProgram {
+    /// Computes the metadata of the program without enforcing the fixed cost of entry points.
+    /// Function costs in it are the static gas costs of running each function.
+    pub fn metadata_without_entry_point_costs(&self) -> Result<Metadata> {
+        metadata_without_entry_point_costs(&self.program, &self.sierra_version)
+    }
+}
+
+/// Contract compiled to its `CasmContractClass` by the latest bundled compiler, along with the CASM
+/// PCs each Sierra statement compiled to.
+pub(crate) struct CompiledContractClass {
+    pub entry_points_by_type: ContractEntryPoints,
+    pub program: Program,
+    pub casm_class: CasmContractClass,
+    pub statement_pc_ranges: Vec<Range<usize>>,
+    sierra_version: Vec<u8>,
+}
+
+impl CompiledContractClass {
+    /// Computes the metadata of the program without enforcing the fixed cost of entry points.
+    /// Function costs in it are the static gas costs of running each function.
+    pub fn metadata_without_entry_point_costs(&self) -> Result<Metadata> {
+        metadata_without_entry_point_costs(&self.program, &self.sierra_version)
+    }
+}
+
+fn metadata_without_entry_point_costs(
+    program: &Program,
+    sierra_version: &[u8],
+) -> Result<Metadata> {
+    let program_info =
+        ProgramRegistryInfo::new(program).with_context(|| "Failed building registry.")?;
+    let linear_solver = uses_linear_solver(sierra_version);
+    let metadata_config = MetadataComputationConfig {
+        linear_gas_solver: linear_solver,
+        linear_ap_change_solver: linear_solver,
+        ..MetadataComputationConfig::default()
+    };
+
+    catch_backend_panic(&LatestBackend, sierra_version, |_| {
+        calc_metadata(program, &program_info, metadata_config)
+            .map_err(|error| with_failing_statement(error, program))
+    })
 }
 
 /// Compiles Sierra of the Starknet contract.
 #[tracing::instrument(skip_all, level = "info")]
-pub fn compile(mut sierra_json: Value) -> Result<Value> {
-    sierra_json["abi"] = Value::Null;
-    sierra_json["sierra_program_debug_info"] = Value::Null;
-    sierra_json["contract_class_version"] = Value::String(String::new());
+pub fn compile(sierra_json: Value) -> Result<Value> {
+    compile_with_registry(sierra_json, &BackendRegistry::default(), false)
+}
+
+/// Options controlling how [`compile_with_options`] compiles the contract.
+#[derive(Clone, Debug, Default)]
+pub struct CompileContractOptions {
+    /// Fail compilation if the contract uses libfuncs that are not on this list. Without it, the
+    /// [`DEFAULT_LIST`] is used for the Sierra versions of the newest bundled compiler.
+    pub allowed_libfuncs_list: Option<AllowedLibfuncsList>,
+    /// Don't check the libfuncs used by the contract at all.
+    pub skip_allowed_libfuncs_check: bool,
+    /// Compile Sierra versions the bundled compilers don't support with the newest of them,
+    /// instead of failing.
+    pub allow_unsupported_version: bool,
+}
 
-    macro_rules! compile_contract {
-        ($sierra_type:ty, $casm_type:ty) => {{
-            let sierra_class = serde_json::from_value::<$sierra_type>(sierra_json.clone()).unwrap();
-            let casm_class = <$casm_type>::from_contract_class(sierra_class, true).unwrap();
-            return Ok(serde_json::to_value(&casm_class)?);
-        }};
+/// Compiles Sierra of the Starknet contract, then fails if it uses libfuncs that are not allowed
+/// as requested in `options`.
+pub fn compile_with_options(sierra_json: Value, options: &CompileContractOptions) -> Result<Value> {
+    let allowed_libfuncs_list = if options.skip_allowed_libfuncs_check {
+        None
+    } else {
+        allowed_libfuncs::list_to_check(&sierra_json, options.allowed_libfuncs_list.clone())
+    };
+    let casm_json = compile_with_registry(
+        sierra_json.clone(),
+        &BackendRegistry::default(),
+        options.allow_unsupported_version,
+    )?;
+
+    if let Some(allowed_libfuncs_list) = &allowed_libfuncs_list {
+        validate_allowed_libfuncs(&sierra_json, allowed_libfuncs_list)?;
     }
+    Ok(casm_json)
+}
 
+/// Compiles Sierra of the Starknet contract with the backend from `registry` supporting its version.
+/// With `allow_unsupported_version`, versions no backend supports are compiled with the newest one.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile_with_registry(
+    mut sierra_json: Value,
+    registry: &BackendRegistry,
+    allow_unsupported_version: bool,
+) -> Result<Value> {
     let sierra_version = parse_sierra_version(&sierra_json)?;
-    match sierra_version.as_slice() {
-        [1, 2..=9, ..] => {
-            let sierra_class: ContractClass = serde_json::from_value(sierra_json.clone()).unwrap();
-            let populate_debug_info = true;
-            let program = sierra_class
-                .extract_sierra_program(populate_debug_info)
-                .unwrap();
-            let casm_class =
-                CasmContractClass::from_contract_class(sierra_class, program, true, usize::MAX)
-                    .unwrap();
-            Ok(serde_json::to_value(casm_class)?)
+    let backend = match registry.find(&sierra_version) {
+        Some(backend) => backend,
+        None => {
+            ensure_backend_compiled_in(&sierra_version)?;
+            if !allow_unsupported_version {
+                let max_supported_version = registry.max_supported_sierra_version().map_or_else(
+                    || "none".to_string(),
+                    |(major, minor)| format!("{major}.{minor}"),
+                );
+                return Err(ErrorKind::UnsupportedVersion.error(format!(
+                    "Unable to compile Sierra to Casm. No matching ContractClass or CasmContractClass found for version {}. \
+                    The newest supported version is {max_supported_version}, use `--allow-unsupported-version` to compile it with the newest bundled compiler anyway",
+                    format_sierra_version(&sierra_version)
+                )));
+            }
+            registry.newest().ok_or_else(|| {
+                ErrorKind::UnsupportedVersion
+                    .error("Unable to compile Sierra to Casm. No Sierra backends are registered")
+            })?
         }
-        [1, 0..=1, 0] => compile_contract!(ContractClassSierraV1, CasmContractClassSierraV1),
-        [0, ..] => compile_contract!(ContractClassSierraV0, CasmContractClassSierraV0),
-        _ => {
-            anyhow::bail!(
-                "Unable to compile Sierra to Casm. No matching ContractClass or CasmContractClass found for version "
-                    .to_string() + &sierra_version.iter().map(|&num| num.to_string()).collect::<Vec<String>>().join("."),
+    };
+
+    sierra_json["abi"] = Value::Null;
+    sierra_json["sierra_program_debug_info"] = Value::Null;
+    sierra_json["contract_class_version"] = Value::String(String::new());
+
+    catch_backend_panic(backend, &sierra_version, |backend| {
+        backend.compile_contract(sierra_json)
+    })
+}
+
+/// Returns whether the Sierra version of the contract is handled by the latest bundled compiler,
+/// which [`compile_program`] requires.
+pub(crate) fn is_compiled_by_latest_backend(sierra_json: &Value) -> Result<bool> {
+    let sierra_version = parse_sierra_version(sierra_json)?;
+    Ok(matches!(
+        sierra_version.as_slice(),
+        &[major, minor, ..] if LATEST_SIERRA_VERSIONS.contains(&(major, minor))
+    ))
+}
+
+/// Compiles the Sierra program of the contract with the latest bundled compiler.
+/// Only Sierra versions handled by the latest compiler (1.2.0 and newer) are supported: the legacy
+/// compilers build the `CasmContractClass` in one step, with metadata computed internally, and
+/// don't expose the CASM each Sierra statement compiles to. Recompiling the program with them
+/// outside of that step isn't guaranteed to reproduce the CASM of the contract.
+pub(crate) fn compile_program(mut sierra_json: Value) -> Result<CompiledContractProgram> {
+    sierra_json["abi"] = Value::Null;
+    sierra_json["contract_class_version"] = Value::String(String::new());
+
+    let sierra_version = parse_latest_sierra_version(&sierra_json)?;
+    let linear_solver = uses_linear_solver(&sierra_version);
+
+    let sierra_class: ContractClass = json::from_value(sierra_json, "contract class")?;
+    let program = catch_backend_panic(&LatestBackend, &sierra_version, |_| {
+        extract_latest_program(&sierra_class)
+    })?;
+
+    let entry_points = &sierra_class.entry_points_by_type;
+    let function_set_costs = entry_points
+        .constructor
+        .iter()
+        .chain(&entry_points.external)
+        .chain(&entry_points.l1_handler)
+        .map(|entry_point| {
+            let function = program
+                .funcs
+                .get(entry_point.function_idx)
+                .context("Entry point refers to a non-existent function")?;
+            Ok((
+                function.id.clone(),
+                [(CostTokenType::Const, ENTRY_POINT_COST)].into(),
+            ))
+        })
+        .collect::<Result<_>>()?;
+    let metadata_config = MetadataComputationConfig {
+        function_set_costs,
+        linear_gas_solver: linear_solver,
+        linear_ap_change_solver: linear_solver,
+        ..MetadataComputationConfig::default()
+    };
+    let compiled = catch_backend_panic(&LatestBackend, &sierra_version, |_| {
+        compile_raw_program(&program, metadata_config)
+    })?;
+
+    Ok(CompiledContractProgram {
+        sierra_class,
+        program,
+        compiled,
+        sierra_version,
+    })
+}
+
+/// Compiles the contract to its `CasmContractClass` with the latest bundled compiler, keeping the
+/// CASM PCs of each Sierra statement. Like [`compile_program`], only Sierra versions handled by the
+/// latest compiler are supported.
+pub(crate) fn compile_class(mut sierra_json: Value) -> Result<CompiledContractClass> {
+    sierra_json["abi"] = Value::Null;
+    sierra_json["contract_class_version"] = Value::String(String::new());
+
+    let sierra_version = parse_latest_sierra_version(&sierra_json)?;
+    let sierra_class: ContractClass = json::from_value(sierra_json, "contract class")?;
+    let entry_points_by_type = sierra_class.entry_points_by_type.clone();
+    let (program, casm_class, debug_info) =
+        catch_backend_panic(&LatestBackend, &sierra_version, |_| {
+            let program = extract_latest_program(&sierra_class)?;
+            let (casm_class, debug_info) = CasmContractClass::from_contract_class_with_debug_info(
+                sierra_class,
+                program.clone(),
+                true,
+                usize::MAX,
             )
-        }
+            .map_err(|error| with_failing_statement(error, &program))?;
+            Ok((program, casm_class, debug_info))
+        })?;
+    let code_size = debug_info
+        .sierra_statement_info
+        .last()
+        .map_or(0, |statement_info| statement_info.end_offset);
+
+    Ok(CompiledContractClass {
+        entry_points_by_type,
+        program,
+        casm_class,
+        statement_pc_ranges: statement_pc_ranges(&debug_info, code_size),
+        sierra_version,
+    })
+}
+
+/// Parses the Sierra version of the contract, failing unless the latest bundled compiler handles it.
+fn parse_latest_sierra_version(sierra_json: &Value) -> Result<Vec<u8>> {
+    let sierra_version = parse_sierra_version(sierra_json)?;
+    let &[major, minor, ..] = sierra_version.as_slice() else {
+        return Err(ErrorKind::InvalidInput.error("Unable to read Sierra version of the contract"));
+    };
+    if !LATEST_SIERRA_VERSIONS.contains(&(major, minor)) {
+        return Err(ErrorKind::UnsupportedVersion.error(format!(
+            "Unable to analyze compiled contract. It is only supported for Sierra 1.2.0 and newer, \
+            as older compilers don't expose the CASM of each Sierra statement, found version {}",
+            format_sierra_version(&sierra_version)
+        )));
     }
+    Ok(sierra_version)
+}
+
+/// Contracts compiled before Sierra 1.4.0 relied on the equation solver.
+fn uses_linear_solver(sierra_version: &[u8]) -> bool {
+    sierra_version.get(1).is_some_and(|&minor| minor >= 4)
+}
+
+pub fn format_sierra_version(sierra_version: &[u8]) -> String {
+    sierra_version
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
 }
 
 /// Extracts sierra version from the program
 /// It will not be possible to convert sierra 0.1.0 version because it keeps its version only in the first felt252
 /// (as a shortstring) while other versions keep it on the first 3 (major, minor, patch)
 /// That's why it fallbacks to 0 when converting from Value to u8
-fn parse_sierra_version(sierra_json: &Value) -> Result<Vec<u8>> {
+pub fn parse_sierra_version(sierra_json: &Value) -> Result<Vec<u8>> {
     let parsed_values: Vec<u8> = sierra_json["sierra_program"]
         .as_array()
-        .context("Unable to read sierra_program. Make sure it is an array of felts")?
+        .ok_or_else(|| {
+            ErrorKind::InvalidInput
+                .error("Unable to read sierra_program. Make sure it is an array of felts")
+        })?
         .iter()
         .take(3)
-        .map(|x| u8::from_str_radix(&x.as_str().unwrap()[2..], 16).unwrap_or_default())
-        .collect();
+        .enumerate()
+        .map(|(idx, felt)| {
+            let digits = felt
+                .as_str()
+                .and_then(|felt| felt.strip_prefix("0x"))
+                .ok_or_else(|| {
+                    ErrorKind::InvalidInput.error(format!(
+                        "Unable to read sierra_program[{idx}]. Make sure it is a hex encoded felt"
+                    ))
+                })?;
+            Ok(u8::from_str_radix(digits, 16).unwrap_or_default())
+        })
+        .collect::<Result<_>>()?;
 
     Ok(parsed_values)
 }
+
+/// Parses a Sierra version given as an argument in the `MAJOR.MINOR.PATCH` format, e.g. `1.0.0`.
+pub fn parse_sierra_version_arg(sierra_version: &str) -> Result<Vec<u8>> {
+    sierra_version
+        .split('.')
+        .map(str::parse::<u8>)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .filter(|parsed_sierra_version| parsed_sierra_version.len() == 3)
+        .ok_or_else(|| {
+            ErrorKind::Usage.error(format!(
+                "Invalid Sierra version {sierra_version}, expected the MAJOR.MINOR.PATCH format"
+            ))
+        })
+}
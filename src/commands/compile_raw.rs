@@ -1,18 +1,34 @@
+use crate::backend::{
+    catch_backend_panic, catch_latest_backend_panic, ensure_backend_compiled_in, BackendRegistry,
+};
+use crate::commands::compile_contract::parse_sierra_version_arg;
+use crate::commands::Emit;
+use crate::error_kind::ErrorKind;
+use crate::failing_statement::with_failing_statement;
+use crate::json;
 use anyhow::{Context, Result};
+use cairo_lang_sierra::debug_info::DebugInfo;
+use cairo_lang_sierra::ids::FunctionId;
 use cairo_lang_sierra::program::Program;
-use cairo_lang_sierra_to_casm::compiler::{CairoProgramDebugInfo, SierraToCasmConfig};
+use cairo_lang_sierra::ProgramParser;
+use cairo_lang_sierra_to_casm::compiler::{
+    CairoProgram, CairoProgramDebugInfo, SierraToCasmConfig,
+};
 use cairo_lang_sierra_to_casm::metadata::{calc_metadata, Metadata, MetadataComputationConfig};
 use cairo_lang_sierra_type_size::ProgramRegistryInfo;
 use clap::Args;
+use lalrpop_util::ParseError;
 use serde_json::{json, Map, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
 use std::path::PathBuf;
 use tracing::trace_span;
 
-#[derive(Args)]
+#[derive(Args, Clone)]
 pub struct CompileRaw {
     /// Path to the sierra program json file, which should have
-    /// `type_declarations`, `libfunc_declarations`, `statements` and `funcs` fields
+    /// `type_declarations`, `libfunc_declarations`, `statements` and `funcs` fields,
+    /// or to the program in the textual Sierra form
     #[arg(short, long)]
     pub sierra_path: PathBuf,
 
@@ -24,18 +40,254 @@ pub struct CompileRaw {
     /// Directory where compiled CASM entries should be cached.
     #[arg(long)]
     pub cache_dir: Option<PathBuf>,
+
+    /// Format of the compilation result.
+    #[arg(long, value_enum, default_value_t)]
+    pub emit: Emit,
+
+    /// Include gas costs and ap changes computed for individual Sierra statements
+    /// in the `statement_metadata` field of the compilation result
+    #[arg(long)]
+    pub statement_metadata: bool,
+
+    /// Sierra version of the program, e.g. `1.0.0`, selecting the compiler it is compiled with.
+    /// Raw programs don't carry their version, so without it the latest compiler is used
+    #[arg(long)]
+    pub sierra_version: Option<String>,
+
+    /// Keep running and recompile the Sierra program whenever it changes.
+    #[arg(long)]
+    pub watch: bool,
+}
+
+/// Options controlling what the compilation result of [`compile`] contains.
+#[derive(Clone, Debug, Default)]
+pub struct CompileRawOptions {
+    /// Include gas costs and ap changes computed for individual Sierra statements.
+    pub statement_metadata: bool,
+}
+
+/// Sierra program compiled to CASM, along with the metadata it was compiled with.
+pub struct CompiledProgram {
+    pub metadata: Metadata,
+    pub cairo_program: CairoProgram,
+}
+
+impl CompiledProgram {
+    /// Returns the range of CASM PCs generated for each Sierra statement, indexed by statement.
+    pub fn statement_pc_ranges(&self) -> Vec<Range<usize>> {
+        let code_size = self
+            .cairo_program
+            .instructions
+            .iter()
+            .map(|instruction| instruction.body.op_size())
+            .sum();
+        statement_pc_ranges(&self.cairo_program.debug_info, code_size)
+    }
+}
+
+/// Returns the range of CASM PCs generated for each Sierra statement, indexed by statement.
+/// `code_size` is where the code of the last statement ends, before any constants.
+pub fn statement_pc_ranges(
+    debug_info: &CairoProgramDebugInfo,
+    code_size: usize,
+) -> Vec<Range<usize>> {
+    let statements_info = &debug_info.sierra_statement_info;
+
+    statements_info
+        .iter()
+        .enumerate()
+        .map(|(statement_idx, statement_info)| {
+            let end = statements_info
+                .get(statement_idx + 1)
+                .map_or(code_size, |next| next.start_offset);
+            statement_info.start_offset..end
+        })
+        .collect()
+}
+
+/// Returns the range of Sierra statements belonging to each function, indexed like `funcs`.
+/// A function is assumed to span from its entry point to the next function's entry point.
+pub fn function_statement_ranges(sierra_program: &Program) -> Vec<Range<usize>> {
+    let mut entry_points: Vec<usize> = sierra_program
+        .funcs
+        .iter()
+        .map(|function| function.entry_point.0)
+        .collect();
+    entry_points.sort_unstable();
+
+    sierra_program
+        .funcs
+        .iter()
+        .map(|function| {
+            let start = function.entry_point.0;
+            let end = entry_points
+                .iter()
+                .copied()
+                .find(|&entry_point| entry_point > start)
+                .unwrap_or(sierra_program.statements.len());
+            start..end
+        })
+        .collect()
+}
+
+/// Deserializes a Sierra program given as a bare `Program`, or as a `VersionedProgram` or
+/// `ProgramArtifact` like the `.sierra.json` files written by Scarb. Debug names embedded in the
+/// artifact's `debug_info` are populated into the program.
+pub fn deserialize_program(mut sierra_program: Value) -> Result<Program> {
+    if let Some(version) = sierra_program.get("version") {
+        if version != 1 {
+            return Err(ErrorKind::InvalidInput.error(format!(
+                "Unsupported version of the Sierra program artifact: {version}"
+            )));
+        }
+    }
+
+    // The parts of `ProgramArtifact` are deserialized separately, as errors in its flattened
+    // `Program` don't carry the path of the failing field.
+    let debug_info = sierra_program
+        .as_object_mut()
+        .and_then(|sierra_program| sierra_program.remove("debug_info"))
+        .filter(|debug_info| !debug_info.is_null());
+    let mut program: Program = json::from_value(sierra_program, "Sierra program")?;
+    if let Some(debug_info) = debug_info {
+        let debug_info: DebugInfo =
+            json::from_field_value(debug_info, "Sierra program", "debug_info")?;
+        debug_info.populate(&mut program);
+    }
+
+    Ok(program)
+}
+
+/// Returns whether the Sierra program is given in its textual form rather than as JSON.
+pub fn is_program_text(sierra_content: &[u8]) -> bool {
+    sierra_content.trim_ascii_start().first() != Some(&b'{')
+}
+
+/// Parses a Sierra program in its textual form, reporting syntax errors with line and column.
+pub fn parse_program_text(sierra_text: &str) -> Result<Program> {
+    ProgramParser::new().parse(sierra_text).map_err(|error| {
+        let (offset, description) = match error {
+            ParseError::InvalidToken { location } => (location, "Invalid token".to_string()),
+            ParseError::UnrecognizedEof { location, expected } => (
+                location,
+                format!(
+                    "Unexpected end of input, expected one of: {}",
+                    expected.join(", ")
+                ),
+            ),
+            ParseError::UnrecognizedToken {
+                token: (start, token, _),
+                expected,
+            } => (
+                start,
+                format!(
+                    "Unexpected token `{token}`, expected one of: {}",
+                    expected.join(", ")
+                ),
+            ),
+            ParseError::ExtraToken {
+                token: (start, token, _),
+            } => (start, format!("Extra token `{token}`")),
+            ParseError::User { error } => {
+                return ErrorKind::InvalidInput
+                    .error(format!("Unable to parse Sierra program: {error}"))
+            }
+        };
+
+        let preceding_text = &sierra_text[..offset];
+        let line = preceding_text.matches('\n').count() + 1;
+        let line_start = preceding_text.rfind('\n').map_or(0, |idx| idx + 1);
+        let column = preceding_text[line_start..].chars().count() + 1;
+        ErrorKind::InvalidInput.error(format!(
+            "Unable to parse Sierra program at line {line}, column {column}: {description}"
+        ))
+    })
+}
+
+/// Compiles Sierra of the plain Cairo code, including the optional outputs selected in `options`.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile(sierra_program: &Program, options: &CompileRawOptions) -> Result<Value> {
+    catch_latest_backend_panic(|| compile_uncaught(sierra_program, options))
+}
+
+/// Like [`compile`], leaving panics to the caller, which knows the Sierra version compiled.
+pub(crate) fn compile_uncaught(
+    sierra_program: &Program,
+    options: &CompileRawOptions,
+) -> Result<Value> {
+    let CompiledProgram {
+        metadata,
+        cairo_program,
+    } = compile_program(sierra_program, MetadataComputationConfig::default())?;
+
+    let span = trace_span!("assemble_cairo_program");
+    let assembled_cairo_program = {
+        let _g = span.enter();
+        cairo_program.assemble()
+    };
+
+    let span = trace_span!("serialize_result");
+    Ok({
+        let _g = span.enter();
+        let mut output = json!({
+            "assembled_cairo_program": {
+                "bytecode": serde_json::to_value(assembled_cairo_program.bytecode)?,
+                "hints": serde_json::to_value(assembled_cairo_program.hints)?
+            },
+            "debug_info": serde_json::to_value(serialize_cairo_program_debug_info(&cairo_program.debug_info))?,
+            "function_costs": serialize_function_costs(sierra_program, &metadata)
+        });
+        if sierra_program
+            .funcs
+            .iter()
+            .any(|function| function.id.debug_name.is_some())
+        {
+            output["functions"] =
+                serialize_functions(sierra_program, &metadata, &cairo_program.debug_info);
+        }
+        if options.statement_metadata {
+            output["statement_metadata"] = serialize_statement_metadata(&metadata);
+        }
+        output
+    })
 }
 
-/// Compiles Sierra of the plain Cairo code.
+/// Compiles Sierra of the plain Cairo code with the backend from `registry` supporting
+/// `sierra_version`, e.g. `1.0.0`. Older programs are compiled by the legacy compilers matching
+/// them, with the same output as [`compile`] except for fields those compilers can't provide.
 #[tracing::instrument(skip_all, level = "info")]
-pub fn compile(sierra_program: &Program) -> Result<Value> {
-    let metadata_config = MetadataComputationConfig::default();
+pub fn compile_with_registry(
+    sierra_program: Value,
+    sierra_version: &str,
+    options: &CompileRawOptions,
+    registry: &BackendRegistry,
+) -> Result<Value> {
+    let parsed_sierra_version = parse_sierra_version_arg(sierra_version)?;
+
+    let Some(backend) = registry.find(&parsed_sierra_version) else {
+        ensure_backend_compiled_in(&parsed_sierra_version)?;
+        return Err(ErrorKind::UnsupportedVersion.error(format!(
+            "Unable to compile Sierra to Casm. No compiler supports Sierra version {sierra_version}"
+        )));
+    };
+    catch_backend_panic(backend, &parsed_sierra_version, |backend| {
+        backend.compile_raw(sierra_program, options)
+    })
+}
+
+/// Computes the metadata of the Sierra program and compiles it to CASM, without assembling it.
+pub(crate) fn compile_program(
+    sierra_program: &Program,
+    metadata_config: MetadataComputationConfig,
+) -> Result<CompiledProgram> {
     let span = trace_span!("calc_metadata");
     let program_info =
         ProgramRegistryInfo::new(sierra_program).with_context(|| "Failed building registry.")?;
     let metadata = {
         let _g = span.enter();
-        calc_metadata(sierra_program, &program_info, metadata_config)?
+        calc_metadata(sierra_program, &program_info, metadata_config)
+            .map_err(|error| with_failing_statement(error, sierra_program))?
     };
 
     let span = trace_span!("compile_sierra_to_casm");
@@ -49,25 +301,13 @@ pub fn compile(sierra_program: &Program) -> Result<Value> {
                 gas_usage_check: true,
                 max_bytecode_size: usize::MAX,
             },
-        )?
-    };
-    let span = trace_span!("assemble_cairo_program");
-    let assembled_cairo_program = {
-        let _g = span.enter();
-        cairo_program.assemble()
+        )
+        .map_err(|error| with_failing_statement(*error, sierra_program))?
     };
 
-    let span = trace_span!("serialize_result");
-    Ok({
-        let _g = span.enter();
-        json!({
-            "assembled_cairo_program": {
-                "bytecode": serde_json::to_value(assembled_cairo_program.bytecode)?,
-                "hints": serde_json::to_value(assembled_cairo_program.hints)?
-            },
-            "debug_info": serde_json::to_value(serialize_cairo_program_debug_info(&cairo_program.debug_info))?,
-            "function_costs": serialize_function_costs(sierra_program, &metadata)
-        })
+    Ok(CompiledProgram {
+        metadata,
+        cairo_program,
     })
 }
 
@@ -79,12 +319,66 @@ fn serialize_function_costs(
         .funcs
         .iter()
         .map(|function| {
-            let costs = metadata.gas_info.function_costs[&function.id]
-                .iter()
-                .map(|(token_type, value)| (token_type.name(), Value::from(*value)))
-                .collect();
+            (
+                function.entry_point.0,
+                serialize_costs(metadata, &function.id),
+            )
+        })
+        .collect()
+}
+
+fn serialize_costs(metadata: &Metadata, function_id: &FunctionId) -> Map<String, Value> {
+    metadata.gas_info.function_costs[function_id]
+        .iter()
+        .map(|(token_type, value)| (token_type.name(), Value::from(*value)))
+        .collect()
+}
+
+/// Serializes costs of each function along with the details needed to identify it without
+/// cross-referencing the Sierra program.
+fn serialize_functions(
+    sierra_program: &Program,
+    metadata: &Metadata,
+    debug_info: &CairoProgramDebugInfo,
+) -> Value {
+    sierra_program
+        .funcs
+        .iter()
+        .map(|function| {
+            json!({
+                "id": function.id.id,
+                "name": function.id.debug_name.as_deref(),
+                "entry_point": function.entry_point.0,
+                "casm_offset": debug_info.sierra_statement_info[function.entry_point.0].start_offset,
+                "costs": serialize_costs(metadata, &function.id),
+            })
+        })
+        .collect()
+}
+
+/// Serializes the gas variable values and ap changes the compiler computed for Sierra statements,
+/// ordered by statement index. Only statements that have any of them are included.
+fn serialize_statement_metadata(metadata: &Metadata) -> Value {
+    let mut statements: BTreeMap<usize, (Map<String, Value>, Option<usize>)> = BTreeMap::new();
+    for ((statement_idx, token_type), value) in metadata.gas_info.variable_values.iter() {
+        statements
+            .entry(statement_idx.0)
+            .or_default()
+            .0
+            .insert(token_type.name(), Value::from(*value));
+    }
+    for (statement_idx, ap_change) in metadata.ap_change_info.variable_values.iter() {
+        statements.entry(statement_idx.0).or_default().1 = Some(*ap_change);
+    }
 
-            (function.entry_point.0, costs)
+    statements
+        .into_iter()
+        .map(|(statement_idx, (gas, ap_change))| {
+            json!({
+                "statement_idx": statement_idx,
+                "gas": gas,
+                "ap_change": ap_change,
+            })
         })
         .collect()
 }
@@ -0,0 +1,196 @@
+use crate::commands::SierraKind;
+use anyhow::{Context, Error, Result};
+use clap::Args;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const ARTIFACTS_INDEX_SUFFIX: &str = ".starknet_artifacts.json";
+const CONTRACT_CLASS_SUFFIX: &str = ".contract_class.json";
+const SIERRA_PROGRAM_SUFFIX: &str = ".sierra.json";
+const COMPILED_CONTRACT_CLASS_SUFFIX: &str = ".compiled_contract_class.json";
+
+#[derive(Args, Clone)]
+pub struct CompileTarget {
+    /// Path to the Scarb target directory of a profile, e.g. `target/dev`
+    #[arg(short, long)]
+    pub target_dir: PathBuf,
+
+    /// Directory where compiled CASM entries should be cached.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Number of artifacts compiled in parallel. Defaults to the number of available CPUs
+    #[arg(short, long)]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Keep running and recompile the artifacts that change or appear in the target directory.
+    #[arg(long)]
+    pub watch: bool,
+}
+
+/// Sierra artifact of a Scarb target directory along with where its compilation result goes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TargetArtifact {
+    pub sierra_path: PathBuf,
+    pub output_path: PathBuf,
+    pub kind: SierraKind,
+}
+
+/// Outcome of compiling the artifacts of a target directory.
+#[derive(Debug, Default)]
+pub struct TargetSummary {
+    pub compiled: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, Error)>,
+}
+
+impl fmt::Display for TargetSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.compiled.len() + self.failed.len();
+        write!(f, "Compiled {} of {total} artifacts", self.compiled.len())?;
+        if !self.failed.is_empty() {
+            write!(f, ", {} failed", self.failed.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the contracts and Sierra programs in a Scarb target directory. Contracts are taken from
+/// the `*.starknet_artifacts.json` indexes, or found by scanning for `*.contract_class.json` if
+/// there are none. Programs are not indexed by Scarb, so `*.sierra.json` files are always scanned.
+pub fn find_artifacts(target_dir: &Path) -> Result<Vec<TargetArtifact>> {
+    let file_names = std::fs::read_dir(target_dir)
+        .with_context(|| format!("Unable to read target directory: {}", target_dir.display()))?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .with_context(|| format!("Unable to read target directory: {}", target_dir.display()))?;
+
+    let index_names: Vec<_> = file_names
+        .iter()
+        .filter(|file_name| file_name.ends_with(ARTIFACTS_INDEX_SUFFIX))
+        .collect();
+    let contract_names = if index_names.is_empty() {
+        file_names
+            .iter()
+            .filter(|file_name| file_name.ends_with(CONTRACT_CLASS_SUFFIX))
+            .cloned()
+            .collect()
+    } else {
+        let mut contract_names = vec![];
+        for index_name in index_names {
+            contract_names.extend(read_artifacts_index(&target_dir.join(index_name))?);
+        }
+        contract_names
+    };
+
+    // Keyed by path, so that contracts listed by several indexes are compiled once.
+    let mut artifacts = BTreeMap::new();
+    for contract_name in contract_names {
+        artifacts.insert(target_dir.join(contract_name), SierraKind::Contract);
+    }
+    for program_name in file_names
+        .iter()
+        .filter(|file_name| file_name.ends_with(SIERRA_PROGRAM_SUFFIX))
+    {
+        artifacts.insert(target_dir.join(program_name), SierraKind::Raw);
+    }
+
+    Ok(artifacts
+        .into_iter()
+        .map(|(sierra_path, kind)| TargetArtifact {
+            output_path: output_path(&sierra_path),
+            sierra_path,
+            kind,
+        })
+        .collect())
+}
+
+/// Returns the file names of the Sierra contract classes listed by the index.
+fn read_artifacts_index(index_path: &Path) -> Result<Vec<String>> {
+    let index: Value = std::fs::read(index_path)
+        .map_err(Error::from)
+        .and_then(|content| Ok(serde_json::from_slice(&content)?))
+        .with_context(|| {
+            format!(
+                "Unable to read Starknet artifacts index: {}",
+                index_path.display()
+            )
+        })?;
+
+    index["contracts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|contract| contract["artifacts"].get("sierra"))
+        .map(|sierra_name| {
+            sierra_name
+                .as_str()
+                .map(ToString::to_string)
+                .with_context(|| {
+                    format!(
+                        "Unexpected Sierra artifact {sierra_name} in Starknet artifacts index: {}",
+                        index_path.display()
+                    )
+                })
+        })
+        .collect()
+}
+
+fn output_path(sierra_path: &Path) -> PathBuf {
+    let file_name = sierra_path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stem = [CONTRACT_CLASS_SUFFIX, SIERRA_PROGRAM_SUFFIX, ".json"]
+        .into_iter()
+        .find_map(|suffix| file_name.strip_suffix(suffix))
+        .unwrap_or(&file_name);
+
+    sierra_path.with_file_name(format!("{stem}{COMPILED_CONTRACT_CLASS_SUFFIX}"))
+}
+
+/// Compiles the artifacts with `compile` on `jobs` threads, collecting the results in the order
+/// of `artifacts`.
+pub fn compile_artifacts(
+    artifacts: &[TargetArtifact],
+    jobs: NonZeroUsize,
+    compile: impl Fn(&TargetArtifact) -> Result<()> + Sync,
+) -> TargetSummary {
+    let next_artifact = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(artifacts.len()));
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.get().min(artifacts.len()) {
+            scope.spawn(|| loop {
+                let index = next_artifact.fetch_add(1, Ordering::Relaxed);
+                let Some(artifact) = artifacts.get(index) else {
+                    break;
+                };
+                let result = compile(artifact);
+                results
+                    .lock()
+                    .expect("Compilation threads do not panic while holding the lock")
+                    .push((index, result));
+            });
+        }
+    });
+
+    let mut results = results
+        .into_inner()
+        .expect("Compilation threads do not panic while holding the lock");
+    results.sort_by_key(|(index, _)| *index);
+
+    let mut summary = TargetSummary::default();
+    for (index, result) in results {
+        let sierra_path = artifacts[index].sierra_path.clone();
+        match result {
+            Ok(()) => summary.compiled.push(sierra_path),
+            Err(error) => summary.failed.push((sierra_path, error)),
+        }
+    }
+    summary
+}
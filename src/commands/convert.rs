@@ -0,0 +1,160 @@
+use crate::backend::{catch_backend_panic, ensure_backend_compiled_in, BackendRegistry};
+use crate::commands::compile_contract::{
+    format_sierra_version, parse_sierra_version, parse_sierra_version_arg,
+};
+use crate::commands::compile_raw::{deserialize_program, is_program_text, parse_program_text};
+use crate::error_kind::ErrorKind;
+use crate::json;
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use serde_json::Value;
+use std::path::PathBuf;
+
+#[derive(Args, Clone)]
+pub struct Convert {
+    /// Path to the sierra json file of the contract, which should have `sierra_program` field,
+    /// or to the sierra program in json or textual form
+    #[arg(short, long)]
+    pub sierra_path: PathBuf,
+
+    /// Path to where the converted program will be saved
+    #[arg(short, long)]
+    pub output_path: Option<PathBuf>,
+
+    /// Form to convert the program to
+    #[arg(long, value_enum)]
+    pub to: ConvertTarget,
+
+    /// Sierra version to encode the program as when converting it to felts, e.g. `1.0.0`.
+    /// The newest supported version is used by default
+    #[arg(long)]
+    pub sierra_version: Option<String>,
+}
+
+/// Form of the Sierra program produced by the `convert` subcommand.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum ConvertTarget {
+    /// Serialized `cairo_lang_sierra::program::Program`.
+    ProgramJson,
+    /// Textual Sierra.
+    SierraText,
+    /// The felt252 encoding of contract classes, as `sierra_program` and
+    /// `sierra_program_debug_info` fields.
+    Felts,
+}
+
+/// Result of converting a Sierra program.
+pub enum Converted {
+    Json(Value),
+    Text(String),
+}
+
+/// Converts the Sierra program of a contract class, or a Sierra program given in JSON or textual
+/// form, to `target`. Contract classes are decoded by the backend of their Sierra version, and
+/// programs are encoded as felts by the backend of `sierra_version`, or the newest one.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn convert(
+    sierra_content: &[u8],
+    target: ConvertTarget,
+    sierra_version: Option<&str>,
+    registry: &BackendRegistry,
+) -> Result<Converted> {
+    if is_program_text(sierra_content) {
+        let sierra_text = std::str::from_utf8(sierra_content).context(
+            ErrorKind::InvalidInput
+                .context("Unable to read Sierra program text. Make sure it is valid UTF-8"),
+        )?;
+        let program = serde_json::to_value(parse_program_text(sierra_text)?)?;
+        return convert_program(program, target, sierra_version, registry);
+    }
+
+    let sierra_json: Value = json::from_slice(sierra_content, "JSON")?;
+    if sierra_json.get("sierra_program").is_none() {
+        return convert_program(sierra_json, target, sierra_version, registry);
+    }
+    if sierra_version.is_some() {
+        return Err(ErrorKind::Usage.error(
+            "`--sierra-version` is only supported for Sierra programs, contract classes carry their version",
+        ));
+    }
+    convert_contract_class(sierra_json, target, registry)
+}
+
+fn convert_contract_class(
+    mut sierra_json: Value,
+    target: ConvertTarget,
+    registry: &BackendRegistry,
+) -> Result<Converted> {
+    if target == ConvertTarget::Felts {
+        return Err(ErrorKind::Usage
+            .error("The Sierra program of the contract class is already encoded as felts"));
+    }
+
+    let sierra_version = parse_sierra_version(&sierra_json)?;
+    let Some(backend) = registry.find(&sierra_version) else {
+        ensure_backend_compiled_in(&sierra_version)?;
+        return Err(ErrorKind::UnsupportedVersion.error(format!(
+            "Unable to convert Sierra program. No backend supports Sierra version {}",
+            format_sierra_version(&sierra_version)
+        )));
+    };
+
+    sierra_json["abi"] = Value::Null;
+    sierra_json["contract_class_version"] = Value::String(String::new());
+    let extracted_program = catch_backend_panic(backend, &sierra_version, |backend| {
+        backend.extract_program(sierra_json)
+    })?;
+
+    Ok(if target == ConvertTarget::SierraText {
+        Converted::Text(extracted_program.text)
+    } else {
+        Converted::Json(extracted_program.program)
+    })
+}
+
+fn convert_program(
+    sierra_program: Value,
+    target: ConvertTarget,
+    sierra_version: Option<&str>,
+    registry: &BackendRegistry,
+) -> Result<Converted> {
+    if target == ConvertTarget::Felts {
+        let (backend, parsed_sierra_version) = match sierra_version {
+            Some(sierra_version) => {
+                let parsed_sierra_version = parse_sierra_version_arg(sierra_version)?;
+                let Some(backend) = registry.find(&parsed_sierra_version) else {
+                    ensure_backend_compiled_in(&parsed_sierra_version)?;
+                    return Err(ErrorKind::UnsupportedVersion.error(format!(
+                        "Unable to convert Sierra program. No backend supports Sierra version {sierra_version}"
+                    )));
+                };
+                (backend, parsed_sierra_version)
+            }
+            None => {
+                let backend = registry.newest().ok_or_else(|| {
+                    ErrorKind::UnsupportedVersion.error(
+                        "Unable to convert Sierra program. No Sierra backends are registered",
+                    )
+                })?;
+                let (major, minor) = *backend.supported_sierra_versions().end();
+                (backend, vec![major, minor, 0])
+            }
+        };
+        let encoded_program = catch_backend_panic(backend, &parsed_sierra_version, |backend| {
+            backend.encode_program(sierra_program, &parsed_sierra_version)
+        })?;
+        return Ok(Converted::Json(encoded_program));
+    }
+
+    if sierra_version.is_some() {
+        return Err(
+            ErrorKind::Usage.error("`--sierra-version` is only supported with `--to felts`")
+        );
+    }
+    let program = deserialize_program(sierra_program)?;
+    Ok(if target == ConvertTarget::SierraText {
+        Converted::Text(program.to_string())
+    } else {
+        Converted::Json(serde_json::to_value(&program)?)
+    })
+}
@@ -0,0 +1,461 @@
+use crate::backend::catch_latest_backend_panic;
+use crate::commands::compile_contract::{self, CompiledContractClass};
+use crate::commands::compile_raw::{self, function_statement_ranges, CompiledProgram};
+use crate::contract_costs;
+use crate::error_kind::ErrorKind;
+use anyhow::{Context, Result};
+use cairo_lang_sierra::program::Program;
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use clap::Args;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+#[derive(Args, Clone)]
+pub struct Diff {
+    /// Path to the baseline json file. It can be an output of `compile-contract` or `compile-raw`,
+    /// or Sierra accepted by either of them, which will be compiled first
+    #[arg(long)]
+    pub old_path: PathBuf,
+
+    /// Path to the json file compared against the baseline, in any format accepted by `--old-path`
+    #[arg(long)]
+    pub new_path: PathBuf,
+
+    /// Path to where the diff json file will be saved
+    #[arg(short, long)]
+    pub output_path: Option<PathBuf>,
+
+    /// Fail if bytecode length of any entry point grows by more than this many felts
+    #[arg(long)]
+    pub max_bytecode_length_increase: Option<i64>,
+
+    /// Fail if any gas cost of any entry point grows by more than this value
+    #[arg(long)]
+    pub max_gas_increase: Option<i64>,
+}
+
+/// Limits on how much the compilation output may grow before the diff is considered failing.
+#[derive(Clone, Debug, Default)]
+pub struct DiffThresholds {
+    pub max_bytecode_length_increase: Option<i64>,
+    pub max_gas_increase: Option<i64>,
+}
+
+/// Differences between two compilation outputs.
+#[derive(Clone, Debug)]
+pub struct DiffReport {
+    pub output: Value,
+    /// Descriptions of the exceeded thresholds, empty if the diff is within all of them.
+    pub exceeded_thresholds: Vec<String>,
+}
+
+/// Compilation output reduced to the parts that are compared.
+struct CompiledOutput {
+    bytecode_length: usize,
+    entry_points: BTreeMap<String, EntryPointSummary>,
+    hints: Vec<Value>,
+    compiled_class_hash: Option<String>,
+}
+
+struct EntryPointSummary {
+    offset: usize,
+    bytecode_length: usize,
+    costs: Option<Map<String, Value>>,
+}
+
+/// Compares two compilation outputs (or Sierra inputs, compiled first) and checks the differences
+/// against `thresholds`.
+///
+/// Bytecode length of an entry point is the length of the code of its own function, not including
+/// the functions it calls. Without Sierra, i.e. for CASM contract classes, it is only known to span
+/// up to the next entry point.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn diff(old_json: Value, new_json: Value, thresholds: &DiffThresholds) -> Result<DiffReport> {
+    let old = CompiledOutput::new(old_json).context("Unable to process the old input")?;
+    let new = CompiledOutput::new(new_json).context("Unable to process the new input")?;
+
+    let mut exceeded_thresholds = vec![];
+    let names: BTreeSet<&String> = old
+        .entry_points
+        .keys()
+        .chain(new.entry_points.keys())
+        .collect();
+    let mut entry_points = vec![];
+    for name in names {
+        let old_entry_point = old.entry_points.get(name);
+        let new_entry_point = new.entry_points.get(name);
+        let mut entry_point_diff = json!({
+            "name": name,
+            "old": old_entry_point.map(EntryPointSummary::to_json),
+            "new": new_entry_point.map(EntryPointSummary::to_json),
+        });
+
+        if let (Some(old_entry_point), Some(new_entry_point)) = (old_entry_point, new_entry_point) {
+            let bytecode_length_delta = signed_delta(
+                old_entry_point.bytecode_length,
+                new_entry_point.bytecode_length,
+            );
+            if let Some(max) = thresholds.max_bytecode_length_increase {
+                if bytecode_length_delta > max {
+                    exceeded_thresholds.push(format!(
+                        "Bytecode length of {name} increased by {bytecode_length_delta}, more than {max}"
+                    ));
+                }
+            }
+            entry_point_diff["bytecode_length_delta"] = bytecode_length_delta.into();
+
+            if let (Some(old_costs), Some(new_costs)) =
+                (&old_entry_point.costs, &new_entry_point.costs)
+            {
+                let gas_deltas = costs_delta(old_costs, new_costs);
+                if let Some(max) = thresholds.max_gas_increase {
+                    for (token_type, delta) in &gas_deltas {
+                        if *delta > max {
+                            exceeded_thresholds.push(format!(
+                                "{token_type} gas cost of {name} increased by {delta}, more than {max}"
+                            ));
+                        }
+                    }
+                }
+                entry_point_diff["gas_deltas"] = json!(gas_deltas);
+            }
+        }
+
+        entry_points.push(entry_point_diff);
+    }
+
+    let (removed_hints, added_hints) = hints_delta(&old.hints, &new.hints);
+    let compiled_class_hash = match (&old.compiled_class_hash, &new.compiled_class_hash) {
+        (Some(old_hash), Some(new_hash)) => json!({
+            "old": old_hash,
+            "new": new_hash,
+            "changed": old_hash != new_hash,
+        }),
+        _ => Value::Null,
+    };
+
+    let output = json!({
+        "bytecode_length": {
+            "old": old.bytecode_length,
+            "new": new.bytecode_length,
+            "delta": signed_delta(old.bytecode_length, new.bytecode_length),
+        },
+        "compiled_class_hash": compiled_class_hash,
+        "entry_points": entry_points,
+        "hints": {
+            "removed": removed_hints,
+            "added": added_hints,
+        },
+        "exceeded_thresholds": exceeded_thresholds,
+    });
+
+    Ok(DiffReport {
+        output,
+        exceeded_thresholds,
+    })
+}
+
+impl CompiledOutput {
+    /// Recognizes the kind of `json` by its fields, compiling it first if it is Sierra.
+    fn new(json: Value) -> Result<Self> {
+        if json.get("sierra_program").is_some() {
+            // Per-function lengths and gas costs are only available for the Sierra versions the
+            // latest compiler handles, the legacy ones don't expose the CASM of each statement.
+            if compile_contract::is_compiled_by_latest_backend(&json)? {
+                Self::from_compiled_contract_class(&compile_contract::compile_class(json)?)
+            } else {
+                Self::from_casm_contract_class(&crate::json::from_value(
+                    compile_contract::compile(json)?,
+                    "CASM contract class",
+                )?)
+            }
+        } else if json.get("entry_points_by_type").is_some() {
+            Self::from_casm_contract_class(&crate::json::from_value(json, "CASM contract class")?)
+        } else if json.get("funcs").is_some() {
+            let sierra_program = compile_raw::deserialize_program(json)?;
+            let compiled = catch_latest_backend_panic(|| {
+                compile_raw::compile_program(&sierra_program, MetadataComputationConfig::default())
+            })?;
+            Self::from_compiled_program(&sierra_program, &compiled)
+        } else if json.get("assembled_cairo_program").is_some() {
+            Self::from_compiled_raw(&json)
+        } else {
+            Err(ErrorKind::InvalidInput.error(
+                "Unrecognized input. Expected an output of `compile-contract` or `compile-raw`, or Sierra accepted by them",
+            ))
+        }
+    }
+
+    /// Without the Sierra, an entry point is only known to span up to the next entry point.
+    fn from_casm_contract_class(casm_class: &CasmContractClass) -> Result<Self> {
+        let entry_points = &casm_class.entry_points_by_type;
+        let offsets = [
+            ("CONSTRUCTOR", &entry_points.constructor),
+            ("EXTERNAL", &entry_points.external),
+            ("L1_HANDLER", &entry_points.l1_handler),
+        ]
+        .into_iter()
+        .flat_map(|(entry_point_type, entry_points)| {
+            entry_points.iter().map(move |entry_point| {
+                (
+                    format!("{entry_point_type} {:#x}", entry_point.selector),
+                    entry_point.offset,
+                )
+            })
+        })
+        .collect();
+
+        Ok(Self {
+            bytecode_length: casm_class.bytecode.len(),
+            entry_points: summarize_entry_points(offsets, casm_class.bytecode.len()),
+            hints: serialize_hints(&serde_json::to_value(&casm_class.hints)?),
+            compiled_class_hash: Some(format!("{:#x}", casm_class.compiled_class_hash())),
+        })
+    }
+
+    fn from_compiled_contract_class(compiled_class: &CompiledContractClass) -> Result<Self> {
+        let contract_costs = contract_costs::from_compiled(
+            &compiled_class.entry_points_by_type,
+            &compiled_class.program,
+            &compiled_class.statement_pc_ranges,
+            &compiled_class.metadata_without_entry_point_costs()?,
+        );
+        let mut entry_points = BTreeMap::new();
+        for (entry_point_type, type_entry_points) in contract_costs["entry_points_by_type"]
+            .as_object()
+            .into_iter()
+            .flatten()
+        {
+            for entry_point in type_entry_points.as_array().into_iter().flatten() {
+                let name = format!(
+                    "{entry_point_type} {}",
+                    entry_point["selector"].as_str().unwrap_or_default()
+                );
+                let summary = EntryPointSummary {
+                    offset: entry_point["offset"].as_u64().unwrap_or_default() as usize,
+                    bytecode_length: entry_point["bytecode_length"].as_u64().unwrap_or_default()
+                        as usize,
+                    costs: entry_point["costs"].as_object().cloned(),
+                };
+                entry_points.insert(name, summary);
+            }
+        }
+
+        Ok(Self {
+            entry_points,
+            ..Self::from_casm_contract_class(&compiled_class.casm_class)?
+        })
+    }
+
+    fn from_compiled_program(sierra_program: &Program, compiled: &CompiledProgram) -> Result<Self> {
+        let statement_pc_ranges = compiled.statement_pc_ranges();
+        let code_size = statement_pc_ranges.last().map_or(0, |range| range.end);
+        let entry_points = sierra_program
+            .funcs
+            .iter()
+            .zip(function_statement_ranges(sierra_program))
+            .map(|(function, statements)| {
+                let offset = statement_pc_ranges[statements.start].start;
+                let end = statement_pc_ranges
+                    .get(statements.end)
+                    .map_or(code_size, |range| range.start);
+                let costs = compiled.metadata.gas_info.function_costs[&function.id]
+                    .iter()
+                    .map(|(token_type, value)| (token_type.name(), Value::from(*value)))
+                    .collect();
+                let summary = EntryPointSummary {
+                    offset,
+                    bytecode_length: end - offset,
+                    costs: Some(costs),
+                };
+                (
+                    function_name(function.id.debug_name.as_deref(), function.entry_point.0),
+                    summary,
+                )
+            })
+            .collect();
+        let assembled_cairo_program = compiled.cairo_program.assemble();
+
+        Ok(Self {
+            bytecode_length: assembled_cairo_program.bytecode.len(),
+            entry_points,
+            hints: serialize_hints(&serde_json::to_value(&assembled_cairo_program.hints)?),
+            compiled_class_hash: None,
+        })
+    }
+
+    /// Functions in the output of `compile-raw` are only known by their entry statements, so each
+    /// one spans up to the code of the next function, and the last one up to the end of bytecode.
+    fn from_compiled_raw(compiled: &Value) -> Result<Self> {
+        let bytecode_length =
+            compiled["assembled_cairo_program"]["bytecode"]
+                .as_array()
+                .context(ErrorKind::InvalidInput.context(
+                    "Missing `assembled_cairo_program.bytecode` in the compilation result",
+                ))?
+                .len();
+        let debug_info = compiled["debug_info"].as_array().context(
+            ErrorKind::InvalidInput.context("Missing `debug_info` in the compilation result"),
+        )?;
+        let function_costs = compiled["function_costs"].as_object().context(
+            ErrorKind::InvalidInput.context("Missing `function_costs` in the compilation result"),
+        )?;
+        let names: HashMap<u64, &str> = compiled["functions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|function| {
+                Some((
+                    function["entry_point"].as_u64()?,
+                    function["name"].as_str()?,
+                ))
+            })
+            .collect();
+
+        let mut functions = BTreeMap::new();
+        for (statement_idx, function_costs) in function_costs {
+            let statement_idx: usize = statement_idx.parse().context(
+                ErrorKind::InvalidInput.context("Invalid statement index in `function_costs`"),
+            )?;
+            let offset = debug_info
+                .get(statement_idx)
+                .and_then(|statement_info| statement_info[0].as_u64())
+                .context(
+                    ErrorKind::InvalidInput
+                        .context("Function entry point is missing in `debug_info`"),
+                )?;
+            functions.insert(statement_idx, (offset as usize, function_costs));
+        }
+
+        let mut entry_points = BTreeMap::new();
+        let mut functions = functions.into_iter().peekable();
+        while let Some((statement_idx, (offset, costs))) = functions.next() {
+            let end = functions
+                .peek()
+                .map_or(bytecode_length, |(_, (next_offset, _))| *next_offset);
+            let name = function_name(names.get(&(statement_idx as u64)).copied(), statement_idx);
+            let summary = EntryPointSummary {
+                offset,
+                bytecode_length: end.saturating_sub(offset),
+                costs: costs.as_object().cloned(),
+            };
+            entry_points.insert(name, summary);
+        }
+
+        Ok(Self {
+            bytecode_length,
+            entry_points,
+            hints: serialize_hints(&compiled["assembled_cairo_program"]["hints"]),
+            compiled_class_hash: None,
+        })
+    }
+}
+
+/// Names a function of a raw Sierra program by its debug name, or its entry statement without one.
+fn function_name(debug_name: Option<&str>, entry_statement: usize) -> String {
+    debug_name.map_or_else(
+        || format!("function at statement {entry_statement}"),
+        str::to_string,
+    )
+}
+
+impl EntryPointSummary {
+    fn to_json(&self) -> Value {
+        json!({
+            "offset": self.offset,
+            "bytecode_length": self.bytecode_length,
+            "costs": self.costs,
+        })
+    }
+}
+
+/// Attributes to every entry point the bytecode up to the next entry point, for CASM classes without
+/// the Sierra to tell where the function of the entry point ends.
+fn summarize_entry_points(
+    offsets: Vec<(String, usize)>,
+    bytecode_length: usize,
+) -> BTreeMap<String, EntryPointSummary> {
+    let sorted_offsets: BTreeSet<usize> = offsets.iter().map(|(_, offset)| *offset).collect();
+
+    offsets
+        .into_iter()
+        .map(|(name, offset)| {
+            let end = sorted_offsets
+                .range(offset + 1..)
+                .next()
+                .copied()
+                .unwrap_or(bytecode_length);
+            let summary = EntryPointSummary {
+                offset,
+                bytecode_length: end.saturating_sub(offset),
+                costs: None,
+            };
+            (name, summary)
+        })
+        .collect()
+}
+
+/// Flattens `[[pc, [hint, ...]], ...]` to the list of hints, dropping the PCs, which change
+/// whenever any preceding code does.
+fn serialize_hints(hints: &Value) -> Vec<Value> {
+    hints
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|pc_hints| pc_hints.get(1)?.as_array())
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+/// Returns the hints only present in `old` and the hints only present in `new`, counting duplicates.
+fn hints_delta(old: &[Value], new: &[Value]) -> (Vec<Value>, Vec<Value>) {
+    let count = |hints: &[Value]| {
+        let mut counts: BTreeMap<String, (usize, Value)> = BTreeMap::new();
+        for hint in hints {
+            counts
+                .entry(hint.to_string())
+                .or_insert_with(|| (0, hint.clone()))
+                .0 += 1;
+        }
+        counts
+    };
+    let old_counts = count(old);
+    let new_counts = count(new);
+    let only_in = |counts: &BTreeMap<String, (usize, Value)>,
+                   other: &BTreeMap<String, (usize, Value)>| {
+        counts
+            .iter()
+            .flat_map(|(key, (count, hint))| {
+                let other_count = other.get(key).map_or(0, |(count, _)| *count);
+                std::iter::repeat_n(hint.clone(), count.saturating_sub(other_count))
+            })
+            .collect()
+    };
+
+    (
+        only_in(&old_counts, &new_counts),
+        only_in(&new_counts, &old_counts),
+    )
+}
+
+fn costs_delta(old: &Map<String, Value>, new: &Map<String, Value>) -> BTreeMap<String, i64> {
+    old.keys()
+        .chain(new.keys())
+        .map(|token_type| {
+            let cost = |costs: &Map<String, Value>| {
+                costs
+                    .get(token_type)
+                    .and_then(Value::as_i64)
+                    .unwrap_or_default()
+            };
+            (token_type.clone(), cost(new) - cost(old))
+        })
+        .collect()
+}
+
+fn signed_delta(old: usize, new: usize) -> i64 {
+    new as i64 - old as i64
+}
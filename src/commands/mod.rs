@@ -1,2 +1,61 @@
+use crate::commands::compile_raw::is_program_text;
+use clap::ValueEnum;
+use serde_json::Value;
+
+pub mod compile;
 pub mod compile_contract;
 pub mod compile_raw;
+pub mod compile_target;
+pub mod convert;
+pub mod diff;
+pub mod verify;
+
+/// Format of the compilation result written by the compile subcommands.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Emit {
+    /// Compilation result serialized to JSON.
+    #[default]
+    Json,
+    /// Human-readable CASM listing with PC offsets, hints and Sierra statement comments.
+    CasmText,
+    /// Table of the bytecode length attributed to each function and each libfunc.
+    SizeReport,
+    /// Bytecode length attributed to each function and each libfunc, serialized to JSON.
+    SizeReportJson,
+}
+
+/// Kind of Sierra input, telling the compile subcommand it belongs to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SierraKind {
+    /// Sierra program, bare, as a versioned artifact or in the textual form.
+    Raw,
+    /// Contract class with a felt252 encoded `sierra_program`.
+    Contract,
+}
+
+impl SierraKind {
+    /// Detects the kind of Sierra input from its content. Input that is not JSON is assumed to be
+    /// a Sierra program in the textual form.
+    pub fn detect(sierra_content: &[u8]) -> Option<Self> {
+        if is_program_text(sierra_content) {
+            return Some(Self::Raw);
+        }
+
+        let sierra_json: Value = serde_json::from_slice(sierra_content).ok()?;
+        if sierra_json.get("sierra_program").is_some() {
+            Some(Self::Contract)
+        } else if [
+            "type_declarations",
+            "libfunc_declarations",
+            "statements",
+            "funcs",
+        ]
+        .iter()
+        .all(|field| sierra_json.get(field).is_some())
+        {
+            Some(Self::Raw)
+        } else {
+            None
+        }
+    }
+}
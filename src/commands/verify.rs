@@ -0,0 +1,174 @@
+use crate::commands::compile_contract;
+use anyhow::{Context, Result};
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use clap::Args;
+use serde_json::Value;
+use std::path::PathBuf;
+
+#[derive(Args, Clone)]
+pub struct Verify {
+    /// Path to the sierra json file, which should have
+    /// `sierra_program` and `entry_points_by_type` fields
+    #[arg(short, long)]
+    pub sierra_path: PathBuf,
+
+    /// Path to the casm json file to verify.
+    /// It should be serialized [`cairo_lang_starknet::casm_contract_class::CasmContractClass`]
+    #[arg(short, long)]
+    pub casm_path: PathBuf,
+}
+
+/// Compiles Sierra of the Starknet contract with the compiler matching its version and compares
+/// the result with `casm_json` structurally. Returns the first mismatch, or `None` if they match.
+///
+/// Compared are the bytecode, hints, entry points and, if both classes can be deserialized by the
+/// latest compiler, the compiled class hash. Hex felts are compared by value, regardless of formatting.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn verify(sierra_json: Value, casm_json: Value) -> Result<Option<String>> {
+    let expected = compile_contract::compile(sierra_json)?;
+
+    let mismatch = compare_bytecode(&expected["bytecode"], &casm_json["bytecode"])?
+        .or_else(|| compare_hints(&expected["hints"], &casm_json["hints"]))
+        .or_else(|| compare_entry_points(&expected, &casm_json))
+        .or_else(|| compare_compiled_class_hashes(&expected, &casm_json));
+
+    Ok(mismatch)
+}
+
+fn compare_bytecode(expected: &Value, actual: &Value) -> Result<Option<String>> {
+    let expected = expected
+        .as_array()
+        .context("Bytecode of the compiled Sierra is not an array of felts")?;
+    let Some(actual) = actual.as_array() else {
+        return Ok(Some(
+            "Bytecode is missing or is not an array of felts".to_string(),
+        ));
+    };
+
+    for (idx, (expected_felt, actual_felt)) in expected.iter().zip(actual).enumerate() {
+        if normalize_felt(expected_felt) != normalize_felt(actual_felt) {
+            return Ok(Some(format!(
+                "Bytecode differs at index {idx}: expected {expected_felt}, found {actual_felt}"
+            )));
+        }
+    }
+    if expected.len() != actual.len() {
+        return Ok(Some(format!(
+            "Bytecode length differs: expected {}, found {}",
+            expected.len(),
+            actual.len()
+        )));
+    }
+
+    Ok(None)
+}
+
+/// Compares `[[pc, [hint, ...]], ...]` lists of hints.
+fn compare_hints(expected: &Value, actual: &Value) -> Option<String> {
+    let empty = vec![];
+    let expected = expected.as_array().unwrap_or(&empty);
+    let Some(actual) = actual.as_array() else {
+        return Some("Hints are missing or are not an array".to_string());
+    };
+
+    for (expected_hints, actual_hints) in expected.iter().zip(actual) {
+        let expected_pc = normalize_felt(&expected_hints[0]);
+        let actual_pc = normalize_felt(&actual_hints[0]);
+        if expected_pc != actual_pc {
+            return Some(format!(
+                "Hints differ: expected hints at PC {}, found hints at PC {}",
+                expected_hints[0], actual_hints[0]
+            ));
+        }
+        if expected_hints[1] != actual_hints[1] {
+            return Some(format!(
+                "Hints differ at PC {}: expected {}, found {}",
+                expected_hints[0], expected_hints[1], actual_hints[1]
+            ));
+        }
+    }
+    if expected.len() != actual.len() {
+        return Some(format!(
+            "Number of PCs with hints differs: expected {}, found {}",
+            expected.len(),
+            actual.len()
+        ));
+    }
+
+    None
+}
+
+fn compare_entry_points(expected: &Value, actual: &Value) -> Option<String> {
+    for entry_point_type in ["CONSTRUCTOR", "EXTERNAL", "L1_HANDLER"] {
+        let entry_points = |casm_json: &Value| {
+            casm_json["entry_points_by_type"][entry_point_type]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+        };
+        let expected = entry_points(expected);
+        let actual = entry_points(actual);
+
+        for (idx, (expected_entry_point, actual_entry_point)) in
+            expected.iter().zip(&actual).enumerate()
+        {
+            let matches = normalize_felt(&expected_entry_point["selector"])
+                == normalize_felt(&actual_entry_point["selector"])
+                && normalize_felt(&expected_entry_point["offset"])
+                    == normalize_felt(&actual_entry_point["offset"])
+                && expected_entry_point["builtins"] == actual_entry_point["builtins"];
+            if !matches {
+                return Some(format!(
+                    "{entry_point_type} entry point #{idx} differs: expected {expected_entry_point}, found {actual_entry_point}"
+                ));
+            }
+        }
+        if expected.len() != actual.len() {
+            return Some(format!(
+                "Number of {entry_point_type} entry points differs: expected {}, found {}",
+                expected.len(),
+                actual.len()
+            ));
+        }
+    }
+
+    None
+}
+
+/// The hash also covers parts of the class not compared otherwise, like bytecode segment lengths.
+fn compare_compiled_class_hashes(expected: &Value, actual: &Value) -> Option<String> {
+    let compiled_class_hash = |casm_json: &Value| {
+        serde_json::from_value::<CasmContractClass>(casm_json.clone())
+            .ok()
+            .map(|casm_class| format!("{:#x}", casm_class.compiled_class_hash()))
+    };
+    let expected_hash = compiled_class_hash(expected)?;
+    let actual_hash = compiled_class_hash(actual)?;
+
+    (expected_hash != actual_hash).then(|| {
+        format!("Compiled class hash differs: expected {expected_hash}, found {actual_hash}")
+    })
+}
+
+/// Returns the hex digits of a felt given as a hex string or a number, without the prefix and
+/// leading zeros, so differently formatted felts compare equal.
+fn normalize_felt(felt: &Value) -> Option<String> {
+    match felt {
+        Value::Number(number) => number.as_u64().map(|number| format!("{number:x}")),
+        Value::String(string) => {
+            let Some(digits) = string
+                .strip_prefix("0x")
+                .or_else(|| string.strip_prefix("0X"))
+            else {
+                return Some(string.clone());
+            };
+            let digits = digits.trim_start_matches('0').to_ascii_lowercase();
+            Some(if digits.is_empty() {
+                "0".to_string()
+            } else {
+                digits
+            })
+        }
+        _ => None,
+    }
+}
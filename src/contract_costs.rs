@@ -0,0 +1,72 @@
+//! Static cost estimates of the entry points of a contract.
+
+use crate::commands::compile_contract;
+use crate::commands::compile_raw::function_statement_ranges;
+use anyhow::Result;
+use cairo_lang_sierra::program::Program;
+use cairo_lang_sierra_to_casm::metadata::Metadata;
+use cairo_lang_starknet_classes::contract_class::{ContractEntryPoint, ContractEntryPoints};
+use serde_json::{json, Map, Value};
+use std::ops::Range;
+
+/// Compiles Sierra of the Starknet contract and returns, for every entry point, the function it
+/// calls, where that function starts in the CASM, how long its own code is and its gas costs.
+///
+/// Costs are computed as if entry points had no fixed cost, so they reflect the code of the
+/// function itself (including the functions it calls) rather than `ENTRY_POINT_COST`.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile(sierra_json: Value) -> Result<Value> {
+    let compiled_contract = compile_contract::compile_program(sierra_json)?;
+    Ok(from_compiled(
+        &compiled_contract.sierra_class.entry_points_by_type,
+        &compiled_contract.program,
+        &compiled_contract.compiled.statement_pc_ranges(),
+        &compiled_contract.metadata_without_entry_point_costs()?,
+    ))
+}
+
+/// Returns the entry point cost estimates of the already compiled contract, given the CASM PCs of
+/// its statements and its metadata without entry point costs.
+pub(crate) fn from_compiled(
+    entry_points: &ContractEntryPoints,
+    program: &Program,
+    statement_pc_ranges: &[Range<usize>],
+    metadata: &Metadata,
+) -> Value {
+    let function_ranges = function_statement_ranges(program);
+    let code_size = statement_pc_ranges.last().map_or(0, |range| range.end);
+    let serialize_entry_points = |entry_points: &[ContractEntryPoint]| -> Vec<Value> {
+        entry_points
+            .iter()
+            .map(|entry_point| {
+                let function = &program.funcs[entry_point.function_idx];
+                let statements = &function_ranges[entry_point.function_idx];
+                let offset = statement_pc_ranges[statements.start].start;
+                let end = statement_pc_ranges
+                    .get(statements.end)
+                    .map_or(code_size, |range| range.start);
+                let costs: Map<String, Value> = metadata.gas_info.function_costs[&function.id]
+                    .iter()
+                    .map(|(token_type, value)| (token_type.name(), Value::from(*value)))
+                    .collect();
+                json!({
+                    "selector": format!("{:#x}", entry_point.selector),
+                    "function_idx": entry_point.function_idx,
+                    "function_id": function.id.id,
+                    "function_name": function.id.debug_name.as_deref(),
+                    "offset": offset,
+                    "bytecode_length": end - offset,
+                    "costs": costs,
+                })
+            })
+            .collect()
+    };
+
+    json!({
+        "entry_points_by_type": {
+            "CONSTRUCTOR": serialize_entry_points(&entry_points.constructor),
+            "EXTERNAL": serialize_entry_points(&entry_points.external),
+            "L1_HANDLER": serialize_entry_points(&entry_points.l1_handler),
+        },
+    })
+}
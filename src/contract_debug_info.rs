@@ -0,0 +1,58 @@
+//! Mapping between the Sierra program of a contract and its compiled CASM.
+//!
+//! `CasmContractClass` carries no debug info, so this is produced as a separate output.
+
+use crate::commands::compile_contract;
+use anyhow::Result;
+use cairo_lang_sierra::program::Program;
+use cairo_lang_starknet_classes::contract_class::{ContractEntryPoint, ContractEntryPoints};
+use serde_json::{json, Value};
+use std::ops::Range;
+
+/// Compiles Sierra of the Starknet contract and returns its debug info: the CASM PC range
+/// generated for every Sierra statement, and the function every entry point calls.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile(sierra_json: Value) -> Result<Value> {
+    let compiled_contract = compile_contract::compile_program(sierra_json)?;
+    Ok(from_compiled(
+        &compiled_contract.sierra_class.entry_points_by_type,
+        &compiled_contract.program,
+        &compiled_contract.compiled.statement_pc_ranges(),
+    ))
+}
+
+/// Returns the debug info of the already compiled contract, given the CASM PCs of its statements.
+pub(crate) fn from_compiled(
+    entry_points: &ContractEntryPoints,
+    program: &Program,
+    statement_pc_ranges: &[Range<usize>],
+) -> Value {
+    let serialize_entry_points = |entry_points: &[ContractEntryPoint]| -> Vec<Value> {
+        entry_points
+            .iter()
+            .map(|entry_point| {
+                let function = &program.funcs[entry_point.function_idx];
+                json!({
+                    "selector": format!("{:#x}", entry_point.selector),
+                    "function_idx": entry_point.function_idx,
+                    "function_id": function.id.id,
+                    "function_name": function.id.debug_name.as_deref(),
+                    "statement_idx": function.entry_point.0,
+                    "offset": statement_pc_ranges[function.entry_point.0].start,
+                })
+            })
+            .collect()
+    };
+
+    json!({
+        "sierra_statement_pc_ranges": statement_pc_ranges
+            .iter()
+            .map(|range| (range.start, range.end))
+            .collect::<Vec<_>>(),
+        "entry_points_by_type": {
+            "CONSTRUCTOR": serialize_entry_points(&entry_points.constructor),
+            "EXTERNAL": serialize_entry_points(&entry_points.external),
+            "L1_HANDLER": serialize_entry_points(&entry_points.l1_handler),
+        },
+    })
+}
@@ -0,0 +1,182 @@
+//! Delegation of contracts with Sierra versions no bundled compiler supports to other USC binaries.
+//!
+//! Every USC binary in the delegate directory is asked for the Sierra versions it supports with the
+//! `supported-versions` subcommand, and the first one supporting the version compiles the contract.
+//! When it fails to, this binary exits with the exit code of the delegate.
+
+use crate::backend::BackendRegistry;
+use crate::error_kind::ErrorKind;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+/// Returns the Sierra versions supported by this binary, in the format of `supported-versions`.
+pub fn supported_versions() -> Value {
+    let sierra_versions: Vec<Value> = BackendRegistry::default()
+        .backends()
+        .map(|backend| {
+            let (min_major, min_minor) = *backend.supported_sierra_versions().start();
+            let (max_major, max_minor) = *backend.supported_sierra_versions().end();
+            json!({
+                "min": [min_major, min_minor],
+                "max": [max_major, max_minor],
+                "backend": backend.name(),
+                "cairo_lang_version": backend.cairo_lang_version(),
+            })
+        })
+        .collect();
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "sierra_versions": sierra_versions,
+    })
+}
+
+/// Prefix of the file names of USC binaries. Other files in the delegate directory are never run.
+const USC_FILE_NAME_PREFIX: &str = "universal-sierra-compiler";
+
+/// USC binary compiling contracts with a Sierra version no bundled compiler supports.
+pub struct Delegate {
+    pub path: PathBuf,
+    /// USC version the binary reports, which tells its outputs apart in the cache.
+    pub version: String,
+}
+
+/// Finds the first USC binary in `delegate_dir`, by file name, that supports the Sierra version.
+/// Only files named like USC binaries, e.g. `universal-sierra-compiler-2.10.0`, are run, and the
+/// ones not supporting `supported-versions` are skipped.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn find_delegate(delegate_dir: &Path, sierra_version: &[u8]) -> Result<Option<Delegate>> {
+    let &[major, minor, ..] = sierra_version else {
+        return Ok(None);
+    };
+
+    let mut candidates: Vec<PathBuf> = fs::read_dir(delegate_dir)
+        .with_context(|| {
+            format!(
+                "Unable to read delegate directory: {}",
+                delegate_dir.display()
+            )
+        })?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file() && is_usc_file_name(path))
+        .collect();
+    candidates.sort();
+
+    for candidate in candidates {
+        match delegate_supported_versions(&candidate) {
+            Ok((version, ranges)) if ranges.iter().any(|range| range.contains(&(major, minor))) => {
+                return Ok(Some(Delegate {
+                    path: candidate,
+                    version,
+                }));
+            }
+            Ok(_) => {}
+            Err(error) => {
+                tracing::debug!(
+                    path = %candidate.display(),
+                    %error,
+                    "skipping delegate candidate"
+                );
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+fn is_usc_file_name(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|file_name| file_name.to_str())
+        .is_some_and(|file_name| file_name.starts_with(USC_FILE_NAME_PREFIX))
+}
+
+/// Returns the USC version of the binary and the Sierra versions it supports.
+fn delegate_supported_versions(usc_path: &Path) -> Result<(String, Vec<RangeInclusive<(u8, u8)>>)> {
+    let output = Command::new(usc_path).arg("supported-versions").output()?;
+    if !output.status.success() {
+        bail!("`supported-versions` exited with {}", output.status);
+    }
+    let supported_versions: Value = serde_json::from_slice(&output.stdout)?;
+
+    let version = supported_versions["version"]
+        .as_str()
+        .context("Missing `version`")?
+        .to_string();
+    let ranges = supported_versions["sierra_versions"]
+        .as_array()
+        .context("Missing `sierra_versions`")?
+        .iter()
+        .map(|range| {
+            let bound_version = |bound: &Value| -> Option<(u8, u8)> {
+                let major = u8::try_from(bound.get(0)?.as_u64()?).ok()?;
+                let minor = u8::try_from(bound.get(1)?.as_u64()?).ok()?;
+                Some((major, minor))
+            };
+            let min = bound_version(&range["min"]).context("Invalid `min` version")?;
+            let max = bound_version(&range["max"]).context("Invalid `max` version")?;
+            Ok(min..=max)
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((version, ranges))
+}
+
+/// Failure of a delegate binary to compile the contract. The binary exits with the same exit code.
+#[derive(Debug)]
+pub struct DelegateFailed {
+    status: ExitStatus,
+}
+
+impl fmt::Display for DelegateFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Delegate USC exited with {}", self.status)
+    }
+}
+
+impl std::error::Error for DelegateFailed {}
+
+/// Returns the exit code of the delegate binary whose failure caused `error`, if any.
+pub fn delegate_exit_code(error: &anyhow::Error) -> Option<i32> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<DelegateFailed>())?
+        .status
+        .code()
+}
+
+/// Compiles the contract with the delegate binary and returns its output.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile_contract(usc_path: &Path, sierra_path: &Path) -> Result<Value> {
+    let output = Command::new(usc_path)
+        .arg("compile-contract")
+        .arg("--sierra-path")
+        .arg(sierra_path)
+        .output()
+        .context(ErrorKind::Io.context(format!(
+            "Unable to run delegate USC: {}",
+            usc_path.display()
+        )))?;
+
+    if !output.status.success() {
+        let message = format!(
+            "Delegate USC {} failed to compile the contract: {}",
+            usc_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Err(anyhow::Error::new(DelegateFailed {
+            status: output.status,
+        })
+        .context(ErrorKind::Compilation.context(message)));
+    }
+
+    // Output that isn't CASM is a failure of the delegate, not invalid input.
+    serde_json::from_slice(&output.stdout).context(ErrorKind::Compilation.context(format!(
+        "Unable to deserialize CASM returned by delegate USC: {}",
+        usc_path.display()
+    )))
+}
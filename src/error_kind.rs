@@ -0,0 +1,83 @@
+//! Categories of failures, which the binary tells apart with its exit code.
+//!
+//! Errors are categorized where they are raised, with [`ErrorKind::error`] for new errors or
+//! [`ErrorKind::context`] for errors of the compilers and other crates. I/O and JSON errors are
+//! recognized without that.
+
+use crate::json::JsonError;
+use crate::note::Noted;
+use serde_json::error::Category;
+use std::fmt;
+
+/// Category of a failure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+    /// Arguments that can't be used together or with the input.
+    Usage,
+    /// Input that is not valid JSON, textual Sierra, or a Sierra program or contract class.
+    InvalidInput,
+    /// Sierra version that none of the compilers supports.
+    UnsupportedVersion,
+    /// Failure of compiling a valid input, including any failure not categorized otherwise.
+    Compilation,
+    /// Failure of reading or writing a file, or starting a delegate binary.
+    Io,
+}
+
+impl ErrorKind {
+    /// Returns the category of `error`, taken from the outermost categorized error in its chain,
+    /// looking through the notes added to it.
+    #[must_use]
+    pub fn of(error: &anyhow::Error) -> Self {
+        if let Some(categorized) = error.downcast_ref::<Categorized>() {
+            return categorized.kind;
+        }
+
+        error
+            .chain()
+            .find_map(|cause| {
+                if let Some(noted) = cause.downcast_ref::<Noted>() {
+                    Some(Self::of(noted.error()))
+                } else if cause.is::<std::io::Error>() {
+                    Some(Self::Io)
+                } else if cause.is::<JsonError>() {
+                    Some(Self::InvalidInput)
+                } else {
+                    let json_error = cause.downcast_ref::<serde_json::Error>()?;
+                    Some(match json_error.classify() {
+                        Category::Io => Self::Io,
+                        _ => Self::InvalidInput,
+                    })
+                }
+            })
+            .unwrap_or(Self::Compilation)
+    }
+
+    /// Returns an error of this kind with `message`.
+    pub(crate) fn error(self, message: impl fmt::Display) -> anyhow::Error {
+        anyhow::Error::new(self.context(message))
+    }
+
+    /// Returns `message` as the context of an error, categorizing it as this kind.
+    pub(crate) fn context(self, message: impl fmt::Display) -> Categorized {
+        Categorized {
+            kind: self,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Message of an error of a known category.
+#[derive(Debug)]
+pub(crate) struct Categorized {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl fmt::Display for Categorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Categorized {}
@@ -0,0 +1,151 @@
+//! Context for failures of compiling Sierra to CASM, pointing to the Sierra statement they
+//! occurred at.
+//!
+//! Compiler errors only refer to statements by their index, which is resolved against the program.
+
+use crate::commands::compile_raw::function_statement_ranges;
+use crate::note::with_note;
+use cairo_lang_sierra::program::{Program, Statement, StatementIdx};
+use cairo_lang_sierra_ap_change::ApChangeError;
+use cairo_lang_sierra_gas::CostError;
+use cairo_lang_sierra_to_casm::annotations::AnnotationError;
+use cairo_lang_sierra_to_casm::compiler::CompilationError;
+use cairo_lang_sierra_to_casm::metadata::MetadataError;
+use cairo_lang_starknet_classes::casm_contract_class::StarknetSierraCompilationError;
+use std::error::Error;
+
+/// Number of statements printed before and after the failing one.
+const SURROUNDING_STATEMENTS: usize = 2;
+
+/// Turns a failure of compiling `sierra_program` into an error naming the Sierra function,
+/// statement and libfunc it occurred at, and listing the surrounding statements. Failures that
+/// don't refer to a statement of the program are returned as they are.
+pub fn with_failing_statement<E>(error: E, sierra_program: &Program) -> anyhow::Error
+where
+    E: Error + Send + Sync + 'static,
+{
+    let Some(StatementIdx(statement_idx)) = failing_statement(&error)
+        .filter(|StatementIdx(statement_idx)| *statement_idx < sierra_program.statements.len())
+    else {
+        return error.into();
+    };
+
+    let description = describe_statement(sierra_program, statement_idx);
+    with_note(error, description)
+}
+
+/// Returns the statement a failure of the compiler, of the metadata computation or of compiling a
+/// contract class occurred at.
+fn failing_statement(error: &(dyn Error + 'static)) -> Option<StatementIdx> {
+    if let Some(error) = error.downcast_ref::<CompilationError>() {
+        compilation_error_statement(error)
+    } else if let Some(error) = error.downcast_ref::<MetadataError>() {
+        metadata_error_statement(error)
+    } else {
+        match error.downcast_ref::<StarknetSierraCompilationError>()? {
+            StarknetSierraCompilationError::CompilationError(error) => {
+                compilation_error_statement(error)
+            }
+            StarknetSierraCompilationError::MetadataError(error) => metadata_error_statement(error),
+            _ => None,
+        }
+    }
+}
+
+fn compilation_error_statement(error: &CompilationError) -> Option<StatementIdx> {
+    match error {
+        CompilationError::AnnotationError(error) => annotation_error_statement(error),
+        CompilationError::InvocationError { statement_idx, .. }
+        | CompilationError::ReturnArgumentsNotOnStack { statement_idx, .. }
+        | CompilationError::ReferencesError { statement_idx, .. }
+        | CompilationError::LibfuncInvocationMismatch { statement_idx, .. }
+        | CompilationError::DanglingReferences { statement_idx, .. }
+        | CompilationError::ExpectedBranchAlign {
+            source_statement_idx: statement_idx,
+            ..
+        }
+        | CompilationError::MetadataStatementOutOfBound(statement_idx)
+        | CompilationError::StatementNotSupportingGasVariables(statement_idx)
+        | CompilationError::StatementNotSupportingApChangeVariables(statement_idx) => {
+            Some(*statement_idx)
+        }
+        _ => None,
+    }
+}
+
+fn annotation_error_statement(error: &AnnotationError) -> Option<StatementIdx> {
+    match error {
+        AnnotationError::InconsistentReferencesAnnotation { statement_idx, .. }
+        | AnnotationError::InconsistentEnvironments { statement_idx, .. }
+        | AnnotationError::InconsistentFunctionId { statement_idx, .. }
+        | AnnotationError::InvalidConvergence { statement_idx, .. }
+        | AnnotationError::MissingAnnotationsForStatement(statement_idx)
+        | AnnotationError::MissingReferenceError { statement_idx, .. }
+        | AnnotationError::ReferencesError { statement_idx, .. }
+        | AnnotationError::ApTrackingAlreadyEnabled { statement_idx, .. }
+        | AnnotationError::AnnotationAlreadySet {
+            source_statement_idx: statement_idx,
+            ..
+        }
+        | AnnotationError::OverrideReferenceError {
+            source_statement_idx: statement_idx,
+            ..
+        }
+        | AnnotationError::GasWalletError {
+            source_statement_idx: statement_idx,
+            ..
+        }
+        | AnnotationError::ApChangeError {
+            source_statement_idx: statement_idx,
+            ..
+        } => Some(*statement_idx),
+        _ => None,
+    }
+}
+
+fn metadata_error_statement(error: &MetadataError) -> Option<StatementIdx> {
+    match error {
+        MetadataError::ApChangeError(
+            ApChangeError::StatementOutOfBounds(statement_idx)
+            | ApChangeError::StatementOutOfOrder(statement_idx)
+            | ApChangeError::WrongNumApChangeBranches(statement_idx)
+            | ApChangeError::BadMergeAllocatedLocalsMismatch(statement_idx)
+            | ApChangeError::BadMergeBaseMismatch(statement_idx),
+        )
+        | MetadataError::CostError(
+            CostError::StatementOutOfBounds(statement_idx)
+            | CostError::EnforceWalletValueFailed(statement_idx),
+        ) => Some(*statement_idx),
+        _ => None,
+    }
+}
+
+fn describe_statement(sierra_program: &Program, statement_idx: usize) -> String {
+    let mut description = format!("Failed at Sierra statement #{statement_idx}");
+    if let Some(function) = function_statement_ranges(sierra_program)
+        .iter()
+        .zip(&sierra_program.funcs)
+        .find_map(|(range, function)| range.contains(&statement_idx).then_some(function))
+    {
+        description.push_str(&format!(" in function `{}`", function.id));
+    }
+    match &sierra_program.statements[statement_idx] {
+        Statement::Invocation(invocation) => {
+            description.push_str(&format!(", invoking libfunc `{}`", invocation.libfunc_id));
+        }
+        Statement::Return(_) => description.push_str(", returning"),
+    }
+    description.push(':');
+
+    let first = statement_idx.saturating_sub(SURROUNDING_STATEMENTS);
+    let last = (statement_idx + SURROUNDING_STATEMENTS).min(sierra_program.statements.len() - 1);
+    for (idx, statement) in sierra_program.statements[first..=last]
+        .iter()
+        .enumerate()
+        .map(|(offset, statement)| (first + offset, statement))
+    {
+        let marker = if idx == statement_idx { '>' } else { ' ' };
+        description.push_str(&format!("\n  {marker} {statement}; // {idx}"));
+    }
+    description
+}
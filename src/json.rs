@@ -0,0 +1,147 @@
+//! JSON deserialization with errors pointing to the failing field of the input.
+
+use serde_core::de::DeserializeOwned;
+use serde_json::value::RawValue;
+use serde_json::Value;
+use serde_path_to_error::Segment;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Error of deserializing JSON, with the path of the failing field, e.g.
+/// `entry_points_by_type.EXTERNAL[3].selector`, and its line and column if they are known.
+#[derive(Clone, Debug)]
+pub struct JsonError {
+    what: &'static str,
+    path: String,
+    segments: Vec<Segment>,
+    position: Option<(usize, usize)>,
+    message: String,
+}
+
+impl JsonError {
+    fn new(
+        what: &'static str,
+        path: Option<&serde_path_to_error::Path>,
+        error: &serde_json::Error,
+    ) -> Self {
+        let position = (error.line() != 0).then(|| (error.line(), error.column()));
+        let message = error.to_string();
+        // Errors of JSON read from text end with the position, which is reported separately.
+        let message = match position {
+            Some((line, column)) => message
+                .strip_suffix(&format!(" at line {line} column {column}"))
+                .map_or(message.clone(), ToString::to_string),
+            None => message,
+        };
+
+        Self {
+            what,
+            path: path.map(ToString::to_string).unwrap_or_default(),
+            segments: path
+                .map(|path| path.iter().cloned().collect())
+                .unwrap_or_default(),
+            position,
+            message,
+        }
+    }
+
+    /// Returns the error as an error of deserializing `field` of the value it was nested in.
+    #[must_use]
+    fn in_field(mut self, field: &str) -> Self {
+        self.path = match self.segments.first() {
+            None => field.to_string(),
+            Some(Segment::Seq { .. }) => format!("{field}{}", self.path),
+            Some(_) => format!("{field}.{}", self.path),
+        };
+        self.segments.insert(
+            0,
+            Segment::Map {
+                key: field.to_string(),
+            },
+        );
+        self
+    }
+
+    /// Returns the error with the line and column of the failing field in `source`, the JSON text
+    /// the deserialized value was read from. Errors of deserializing a `serde_json::Value` don't
+    /// know them otherwise.
+    #[must_use]
+    pub fn located(&self, source: &[u8]) -> Option<Self> {
+        if self.position.is_some() || self.segments.is_empty() {
+            return None;
+        }
+        let source = std::str::from_utf8(source).ok()?;
+
+        Some(Self {
+            position: Some(find_position(source, &self.segments)?),
+            ..self.clone()
+        })
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to deserialize {}", self.what)?;
+        if !self.segments.is_empty() {
+            write!(f, " at `{}`", self.path)?;
+        }
+        if let Some((line, column)) = self.position {
+            write!(f, " (line {line}, column {column})")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+/// Deserializes `T` from JSON text. `what` names the input in the error message.
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8], what: &'static str) -> Result<T, JsonError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|error| JsonError::new(what, Some(error.path()), error.inner()))?;
+    deserializer
+        .end()
+        .map_err(|error| JsonError::new(what, None, &error))?;
+
+    Ok(value)
+}
+
+/// Deserializes `T` from a JSON value. `what` names the input in the error message.
+pub fn from_value<T: DeserializeOwned>(value: Value, what: &'static str) -> Result<T, JsonError> {
+    serde_path_to_error::deserialize(value)
+        .map_err(|error| JsonError::new(what, Some(error.path()), error.inner()))
+}
+
+/// Deserializes `T` from `field` of a JSON object, which errors name as `what` and include in the
+/// path of the failing field, as if the whole object was deserialized.
+pub fn from_field_value<T: DeserializeOwned>(
+    value: Value,
+    what: &'static str,
+    field: &str,
+) -> Result<T, JsonError> {
+    from_value(value, what).map_err(|error| error.in_field(field))
+}
+
+/// Returns the 1-based line and column where the value at `segments` starts in `source`.
+fn find_position(source: &str, segments: &[Segment]) -> Option<(usize, usize)> {
+    let mut value: &RawValue = serde_json::from_str(source).ok()?;
+    for segment in segments {
+        value = match segment {
+            Segment::Seq { index } => *serde_json::from_str::<Vec<&RawValue>>(value.get())
+                .ok()?
+                .get(*index)?,
+            Segment::Map { key } | Segment::Enum { variant: key } => {
+                *serde_json::from_str::<HashMap<String, &RawValue>>(value.get())
+                    .ok()?
+                    .get(key)?
+            }
+            Segment::Unknown => break,
+        };
+    }
+
+    let offset = value.get().as_ptr() as usize - source.as_ptr() as usize;
+    let preceding = &source[..offset];
+    let line = preceding.matches('\n').count() + 1;
+    let column = offset - preceding.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+    Some((line, column))
+}
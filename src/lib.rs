@@ -1,4 +1,58 @@
+use anyhow::Result;
+use cairo_lang_sierra::program::Program;
+use serde_json::Value;
+
+mod allowed_libfuncs;
+mod backend;
+mod casm_text;
 mod commands;
+mod contract_costs;
+mod contract_debug_info;
+mod error_kind;
+mod failing_statement;
+mod json;
+mod note;
+mod size_report;
 
+pub use allowed_libfuncs::{
+    find_disallowed_libfuncs, validate_allowed_libfuncs, AllowedLibfuncsList, DisallowedLibfunc,
+    DEFAULT_LIST as DEFAULT_ALLOWED_LIBFUNCS_LIST,
+};
+#[cfg(feature = "sierra-0-1")]
+pub use backend::SierraV0Backend;
+#[cfg(feature = "sierra-1-0")]
+pub use backend::SierraV1Backend;
+pub use backend::{
+    install_quiet_panic_hook, BackendPanic, BackendRegistry, ExtractedProgram, LatestBackend,
+    SierraBackend,
+};
+pub use casm_text::compile_contract as compile_contract_to_casm_text;
+pub use casm_text::compile_raw as compile_raw_to_casm_text;
 pub use commands::compile_contract::compile as compile_contract;
-pub use commands::compile_raw::compile as compile_raw;
+pub use commands::compile_contract::compile_with_options as compile_contract_with_options;
+pub use commands::compile_contract::compile_with_registry as compile_contract_with_registry;
+pub use commands::compile_contract::CompileContractOptions;
+pub use commands::compile_raw::compile as compile_raw_with_options;
+pub use commands::compile_raw::compile_with_registry as compile_raw_with_registry;
+pub use commands::compile_raw::deserialize_program as deserialize_sierra_program;
+pub use commands::compile_raw::CompileRawOptions;
+pub use commands::compile_target::{
+    compile_artifacts as compile_target_artifacts, find_artifacts as find_target_artifacts,
+    TargetArtifact, TargetSummary,
+};
+pub use commands::convert::{convert as convert_sierra_program, ConvertTarget, Converted};
+pub use commands::diff::{diff as diff_compilation_outputs, DiffReport, DiffThresholds};
+pub use commands::verify::verify as verify_contract;
+pub use commands::SierraKind;
+pub use contract_costs::compile as compile_contract_entry_point_costs;
+pub use contract_debug_info::compile as compile_contract_debug_info;
+pub use error_kind::ErrorKind;
+pub use json::JsonError;
+pub use size_report::compile_contract as compile_contract_size_report;
+pub use size_report::compile_raw as compile_raw_size_report;
+pub use size_report::{SizeEntry, SizeReport};
+
+/// Compiles Sierra of the plain Cairo code.
+pub fn compile_raw(sierra_program: &Program) -> Result<Value> {
+    compile_raw_with_options(sierra_program, &CompileRawOptions::default())
+}
@@ -1,19 +1,51 @@
-use anyhow::{Context, Error, Result};
+use anyhow::{anyhow, Context, Error, Result};
 use cairo_lang_sierra::program::Program;
-use clap::{Parser, Subcommand};
-use console::style;
+use cairo_lang_sierra_to_casm::metadata::Metadata;
+use cairo_lang_starknet_classes::contract_class::ContractEntryPoints;
+use clap::{Parser, Subcommand, ValueEnum};
 use mimalloc::MiMalloc;
 use serde_json::Value;
+use std::ffi::{OsStr, OsString};
 use std::fs::File;
 use std::io::{self, BufWriter, Write};
-use std::path::PathBuf;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
+mod allowed_libfuncs;
+mod backend;
 mod cache;
+mod casm_text;
 mod commands;
-
-use cache::SierraKind;
-use commands::compile_contract::CompileContract;
-use commands::compile_raw::CompileRaw;
+mod contract_costs;
+mod contract_debug_info;
+mod delegation;
+mod error_kind;
+mod failing_statement;
+mod json;
+mod messages;
+mod note;
+mod size_report;
+mod watch;
+
+use allowed_libfuncs::AllowedLibfuncsList;
+use backend::BackendRegistry;
+use commands::compile::Compile;
+use commands::compile_contract::{
+    self, CompileContract, CompileContractOptions, CompiledContractClass, CompiledContractProgram,
+};
+use commands::compile_raw::{CompileRaw, CompileRawOptions};
+use commands::compile_target::{CompileTarget, TargetArtifact};
+use commands::convert::{Convert, Converted};
+use commands::diff::{Diff, DiffThresholds};
+use commands::verify::Verify;
+use commands::{Emit, SierraKind};
+use delegation::Delegate;
+use error_kind::ErrorKind;
+use json::JsonError;
+use messages::MessageFormat;
+use size_report::SizeReport;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
@@ -23,25 +55,73 @@ static GLOBAL: MiMalloc = MiMalloc;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Format of the errors, warnings and other diagnostics printed to stderr
+    #[arg(long, global = true, value_enum, default_value_t)]
+    message_format: MessageFormat,
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
 enum Commands {
+    // Compile sierra of the contract or sierra program, detecting which one it is
+    Compile(Compile),
+
     // Compile sierra of the contract
     CompileContract(CompileContract),
 
     // Compile sierra program (cairo_lang_sierra::program::Program)
     CompileRaw(CompileRaw),
-}
 
-fn print_error_message(error: &Error) {
-    let error_tag = style("ERROR").red();
-    eprintln!("[{error_tag}] {error}");
+    // Compile every contract and sierra program of a Scarb target directory
+    CompileTarget(CompileTarget),
+
+    // Compare two compilation outputs, or Sierra compiled to them
+    Diff(Diff),
+
+    // Check that the casm of the contract is what its sierra compiles to
+    Verify(Verify),
+
+    // Convert a sierra program between json, textual and felt252 encoded forms
+    Convert(Convert),
+
+    // Print the sierra versions supported by this binary
+    SupportedVersions,
 }
 
 #[tracing::instrument(skip_all, level = "info")]
 fn deserialize_json<T: for<'de> serde_core::de::Deserialize<'de>>(bytes: &[u8]) -> Result<T> {
-    serde_json::from_slice(bytes).context("Unable to deserialize JSON")
+    Ok(json::from_slice(bytes, "JSON")?)
+}
+
+fn read_sierra_file(sierra_path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(sierra_path).with_context(|| {
+        format!(
+            "Unable to read Sierra input file: {}",
+            sierra_path.display()
+        )
+    })
+}
+
+fn deserialize_sierra_program(sierra_content: &[u8]) -> Result<Program> {
+    if commands::compile_raw::is_program_text(sierra_content) {
+        let sierra_text = std::str::from_utf8(sierra_content).context(
+            ErrorKind::InvalidInput
+                .context("Unable to read Sierra program text. Make sure it is valid UTF-8"),
+        )?;
+        return commands::compile_raw::parse_program_text(sierra_text);
+    }
+
+    commands::compile_raw::deserialize_program(json::from_slice(sierra_content, "Sierra program")?)
+}
+
+/// Reads an input of `diff` as JSON, converting textual Sierra programs to it.
+fn read_diff_input(path: &Path) -> Result<Value> {
+    let content = read_sierra_file(path)?;
+    if commands::compile_raw::is_program_text(&content) {
+        return Ok(serde_json::to_value(deserialize_sierra_program(&content)?)?);
+    }
+
+    deserialize_json(&content)
 }
 
 #[tracing::instrument(skip_all, level = "info")]
@@ -60,53 +140,765 @@ fn output_casm(output: &Value, output_file_path: Option<PathBuf>) -> Result<()>
     Ok(())
 }
 
-fn main_execution() -> Result<bool> {
-    let cli = Cli::parse();
-    let _g = init_logging();
+#[tracing::instrument(skip_all, level = "info")]
+fn output_casm_text(casm_text: &str, output_file_path: Option<PathBuf>) -> Result<()> {
+    if let Some(output_path) = output_file_path {
+        std::fs::write(output_path, casm_text).context("Unable to save casm text file")?;
+    } else {
+        let mut stdout = io::stdout().lock();
+        stdout
+            .write_all(casm_text.as_bytes())
+            .context("Unable to write casm text")?;
+    }
 
-    match cli.command {
-        Commands::CompileContract(compile_contract) => {
-            let sierra_path = compile_contract.sierra_path;
-            let casm_json = cache::compile_with_cache(
-                &sierra_path,
+    Ok(())
+}
+
+fn output_size_report(
+    size_report: &SizeReport,
+    emit: Emit,
+    output_file_path: Option<PathBuf>,
+) -> Result<()> {
+    if emit == Emit::SizeReportJson {
+        output_casm(&size_report.to_json(), output_file_path)
+    } else {
+        output_casm_text(&size_report.to_table(), output_file_path)
+    }
+}
+
+#[tracing::instrument(skip_all, level = "info")]
+fn output_json_file(output: &Value, output_path: &Path, description: &str) -> Result<()> {
+    let file = File::create(output_path)
+        .with_context(|| format!("Unable to open/create {description} json file"))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, output)
+        .with_context(|| format!("Unable to save {description} json file"))?;
+    writer
+        .flush()
+        .with_context(|| format!("Unable to save {description} json file"))?;
+
+    Ok(())
+}
+
+/// Checks the libfuncs used by the contract against the requested allowed libfuncs list, or the
+/// default one. It runs after every compilation, cached or not, before any output is written.
+/// Contracts compiled by a delegate are not checked, as the bundled lists don't cover their version.
+fn check_allowed_libfuncs(
+    compile_contract: &CompileContract,
+    route: Option<&UnsupportedVersionRoute>,
+) -> Result<()> {
+    if compile_contract.skip_allowed_libfuncs_check {
+        return Ok(());
+    }
+    if let Some(UnsupportedVersionRoute::Delegate(delegate)) = route {
+        if compile_contract.allowed_libfuncs_list_name.is_some()
+            || compile_contract.allowed_libfuncs_list_file.is_some()
+        {
+            messages::warning(
+                &format!(
+                    "Allowed libfuncs are not checked for contracts compiled by {} (USC {})",
+                    delegate.path.display(),
+                    delegate.version
+                ),
+                Some(&compile_contract.sierra_path),
+            );
+        }
+        return Ok(());
+    }
+    let allowed_libfuncs_list = compile_contract
+        .allowed_libfuncs_list_name
+        .clone()
+        .map(AllowedLibfuncsList::Name)
+        .or_else(|| {
+            compile_contract
+                .allowed_libfuncs_list_file
+                .clone()
+                .map(AllowedLibfuncsList::File)
+        });
+    let sierra_json = deserialize_json(&read_sierra_file(&compile_contract.sierra_path)?)?;
+    let Some(allowed_libfuncs_list) =
+        allowed_libfuncs::list_to_check(&sierra_json, allowed_libfuncs_list)
+    else {
+        return Ok(());
+    };
+
+    if compile_contract.allow_disallowed_libfuncs {
+        for disallowed_libfunc in
+            allowed_libfuncs::find_disallowed_libfuncs(&sierra_json, &allowed_libfuncs_list)?
+        {
+            messages::warning(
+                &format!("Libfunc {disallowed_libfunc} is not on the {allowed_libfuncs_list}"),
+                Some(&compile_contract.sierra_path),
+            );
+        }
+        Ok(())
+    } else {
+        allowed_libfuncs::validate_allowed_libfuncs(&sierra_json, &allowed_libfuncs_list)
+    }
+}
+
+/// Returns the Sierra version of the contract if no bundled compiler supports it.
+fn unsupported_sierra_version(sierra_path: &Path) -> Result<Option<Vec<u8>>> {
+    let sierra_json = deserialize_json(&read_sierra_file(sierra_path)?)?;
+    let sierra_version = compile_contract::parse_sierra_version(&sierra_json)?;
+
+    Ok(BackendRegistry::default()
+        .find(&sierra_version)
+        .is_none()
+        .then_some(sierra_version))
+}
+
+fn warn_about_unsupported_version(sierra_version: &[u8], sierra_path: &Path) {
+    let (max_major, max_minor) = BackendRegistry::default()
+        .max_supported_sierra_version()
+        .expect("Bundled backends are always registered");
+    messages::warning(
+        &format!(
+            "Sierra version {} is not supported, the newest supported version is {max_major}.{max_minor}. \
+            Compiling it with the newest bundled compiler, the resulting CASM may be incorrect",
+            compile_contract::format_sierra_version(sierra_version)
+        ),
+        Some(sierra_path),
+    );
+}
+
+/// How a contract with a Sierra version no bundled compiler supports is compiled.
+enum UnsupportedVersionRoute {
+    /// By another USC binary supporting the version.
+    Delegate(Delegate),
+    /// By the newest bundled compiler, as `--allow-unsupported-version` requests.
+    Fallback,
+}
+
+/// Returns how the contract is compiled if its Sierra version is unsupported and either a delegate
+/// for it or the fallback to the newest bundled compiler is available. Otherwise the bundled
+/// compilers are used, which fail for unsupported versions.
+fn unsupported_version_route(
+    compile_contract: &CompileContract,
+) -> Result<Option<UnsupportedVersionRoute>> {
+    if !compile_contract.allow_unsupported_version && compile_contract.delegate_dir.is_none() {
+        return Ok(None);
+    }
+    let Some(sierra_version) = unsupported_sierra_version(&compile_contract.sierra_path)? else {
+        return Ok(None);
+    };
+
+    if let Some(delegate_dir) = &compile_contract.delegate_dir {
+        if let Some(delegate) = delegation::find_delegate(delegate_dir, &sierra_version)? {
+            return Ok(Some(UnsupportedVersionRoute::Delegate(delegate)));
+        }
+    }
+    // Versions of backends left out of the build fail instead of falling back.
+    if compile_contract.allow_unsupported_version
+        && backend::disabled_backend_feature(&sierra_version).is_none()
+    {
+        warn_about_unsupported_version(&sierra_version, &compile_contract.sierra_path);
+        return Ok(Some(UnsupportedVersionRoute::Fallback));
+    }
+    Ok(None)
+}
+
+/// Compiles the contract to CASM through the cache, delegating it to another USC binary or falling
+/// back to the newest bundled compiler as `route` tells.
+fn compile_contract_with_cache(
+    compile_contract: &CompileContract,
+    route: Option<&UnsupportedVersionRoute>,
+) -> Result<Value> {
+    let compile = |sierra_content: &[u8]| {
+        if let Some(UnsupportedVersionRoute::Delegate(delegate)) = route {
+            return delegation::compile_contract(&delegate.path, &compile_contract.sierra_path);
+        }
+        // Allowed libfuncs are checked separately, so that cached CASM is checked as well.
+        let options = CompileContractOptions {
+            skip_allowed_libfuncs_check: true,
+            allow_unsupported_version: matches!(route, Some(UnsupportedVersionRoute::Fallback)),
+            ..CompileContractOptions::default()
+        };
+        compile_contract::compile_with_options(deserialize_json(sierra_content)?, &options)
+    };
+
+    // Outputs of delegates and of the fallback to the newest bundled compiler are cached
+    // separately, so that unsupported versions fail rather than hit the cache without them.
+    let cache_variant = match route {
+        Some(UnsupportedVersionRoute::Delegate(delegate)) => {
+            format!("delegate:{}:{}", delegate.version, delegate.path.display())
+        }
+        Some(UnsupportedVersionRoute::Fallback) => "allow-unsupported-version".to_string(),
+        None => {
+            return cache::compile_with_cache(
+                &compile_contract.sierra_path,
                 SierraKind::Contract,
                 compile_contract.cache_dir.as_deref(),
-                |sierra_content| {
-                    let sierra_json = deserialize_json(sierra_content)?;
-                    commands::compile_contract::compile(sierra_json)
-                },
+                compile,
+            )
+        }
+    };
+    cache::compile_variant_with_cache(
+        &compile_contract.sierra_path,
+        SierraKind::Contract,
+        &cache_variant,
+        compile_contract.cache_dir.as_deref(),
+        compile,
+    )
+}
+
+/// Returns whether any contract reports are requested alongside the compilation result.
+fn contract_sidecars_requested(compile_contract: &CompileContract) -> bool {
+    compile_contract.debug_info_path.is_some() || compile_contract.entry_point_costs_path.is_some()
+}
+
+/// Compiles the contract to CASM once for both the compilation result and the requested reports,
+/// which the cached CASM lacks the details for.
+fn compile_contract_class(compile_contract: &CompileContract) -> Result<CompiledContractClass> {
+    let sierra_json: Value = deserialize_json(&read_sierra_file(&compile_contract.sierra_path)?)?;
+    if !compile_contract::is_compiled_by_latest_backend(&sierra_json)? {
+        return Err(ErrorKind::UnsupportedVersion.error(format!(
+            "`--debug-info-path` and `--entry-point-costs-path` are unsupported for Sierra version {}. \
+            They are only supported for Sierra 1.2.0 and newer, as older compilers don't expose the CASM of each Sierra statement",
+            compile_contract::format_sierra_version(&compile_contract::parse_sierra_version(
+                &sierra_json
+            )?)
+        )));
+    }
+
+    compile_contract::compile_class(sierra_json)
+}
+
+/// Writes the optional contract reports requested alongside the compilation result, built from the
+/// same compilation as the result.
+fn output_contract_sidecars(
+    compile_contract: &CompileContract,
+    entry_points: &ContractEntryPoints,
+    program: &Program,
+    statement_pc_ranges: &[Range<usize>],
+    metadata_without_entry_point_costs: impl FnOnce() -> Result<Metadata>,
+) -> Result<()> {
+    if let Some(debug_info_path) = &compile_contract.debug_info_path {
+        let debug_info =
+            contract_debug_info::from_compiled(entry_points, program, statement_pc_ranges);
+        output_json_file(&debug_info, debug_info_path, "debug info")?;
+    }
+    if let Some(entry_point_costs_path) = &compile_contract.entry_point_costs_path {
+        let entry_point_costs = contract_costs::from_compiled(
+            entry_points,
+            program,
+            statement_pc_ranges,
+            &metadata_without_entry_point_costs()?,
+        );
+        output_json_file(
+            &entry_point_costs,
+            entry_point_costs_path,
+            "entry point costs",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes the reports requested alongside the compilation result of the contract compiled for
+/// another output than CASM json.
+fn output_compiled_contract_sidecars(
+    compile_contract: &CompileContract,
+    compiled_contract: &CompiledContractProgram,
+) -> Result<()> {
+    output_contract_sidecars(
+        compile_contract,
+        &compiled_contract.sierra_class.entry_points_by_type,
+        &compiled_contract.program,
+        &compiled_contract.compiled.statement_pc_ranges(),
+        || compiled_contract.metadata_without_entry_point_costs(),
+    )
+}
+
+/// Turns `compile` into the compile subcommand matching the kind of its input.
+fn resolve_compile_command(compile: Compile) -> Result<Commands> {
+    let sierra_kind = SierraKind::detect(&read_sierra_file(&compile.sierra_path)?).ok_or_else(|| {
+        ErrorKind::InvalidInput.error(
+            "Unable to detect the kind of Sierra input. Expected a contract class with `sierra_program` field, \
+            or a Sierra program in json or textual form",
+        )
+    })?;
+
+    Ok(compile_command(sierra_kind, compile))
+}
+
+/// Returns the compile subcommand for the `sierra_kind` input, with the defaults of its options.
+fn compile_command(sierra_kind: SierraKind, compile: Compile) -> Commands {
+    match sierra_kind {
+        SierraKind::Contract => Commands::CompileContract(CompileContract {
+            sierra_path: compile.sierra_path,
+            output_path: compile.output_path,
+            cache_dir: compile.cache_dir,
+            emit: compile.emit,
+            debug_info_path: None,
+            entry_point_costs_path: None,
+            allowed_libfuncs_list_name: None,
+            allowed_libfuncs_list_file: None,
+            allow_disallowed_libfuncs: false,
+            skip_allowed_libfuncs_check: false,
+            allow_unsupported_version: false,
+            delegate_dir: None,
+            watch: false,
+        }),
+        SierraKind::Raw => Commands::CompileRaw(CompileRaw {
+            sierra_path: compile.sierra_path,
+            output_path: compile.output_path,
+            cache_dir: compile.cache_dir,
+            emit: compile.emit,
+            statement_metadata: false,
+            sierra_version: None,
+            watch: false,
+        }),
+    }
+}
+
+/// Compiles every artifact of the Scarb target directory next to it, printing the failures and
+/// a summary. Returns whether all of them compiled.
+fn compile_target_dir(compile_target: &CompileTarget) -> Result<bool> {
+    let artifacts = commands::compile_target::find_artifacts(&compile_target.target_dir)?;
+    if artifacts.is_empty() {
+        return Err(ErrorKind::InvalidInput.error(format!(
+            "No contracts or Sierra programs found in target directory: {}",
+            compile_target.target_dir.display()
+        )));
+    }
+
+    Ok(compile_target_artifacts(compile_target, &artifacts))
+}
+
+fn compile_target_artifacts(compile_target: &CompileTarget, artifacts: &[TargetArtifact]) -> bool {
+    let jobs = compile_target
+        .jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN));
+
+    let summary = commands::compile_target::compile_artifacts(artifacts, jobs, |artifact| {
+        let compile = Compile {
+            sierra_path: artifact.sierra_path.clone(),
+            output_path: Some(artifact.output_path.clone()),
+            cache_dir: compile_target.cache_dir.clone(),
+            emit: Emit::Json,
+            watch: false,
+        };
+        run_command(compile_command(artifact.kind, compile)).map(|_| ())
+    });
+
+    let summary_message = format!("{summary} in {}", compile_target.target_dir.display());
+    let all_compiled = summary.failed.is_empty();
+    for (sierra_path, error) in summary.failed {
+        let note = format!("Unable to compile {}", sierra_path.display());
+        messages::error(&note::with_note(error, note), Some(&sierra_path));
+    }
+    messages::summary(&summary_message);
+
+    all_compiled
+}
+
+/// Recompiles the artifacts of the target directory that change or appear, until interrupted.
+fn watch_target_dir(compile_target: &CompileTarget) -> ! {
+    let find_artifacts = || commands::compile_target::find_artifacts(&compile_target.target_dir);
+
+    watch::watch(
+        || {
+            let artifacts = find_artifacts()?;
+            Ok(artifacts
+                .into_iter()
+                .map(|artifact| artifact.sierra_path)
+                .collect())
+        },
+        |changed| match find_artifacts() {
+            Ok(artifacts) => {
+                let changed_artifacts: Vec<_> = artifacts
+                    .into_iter()
+                    .filter(|artifact| changed.contains(&artifact.sierra_path))
+                    .collect();
+                compile_target_artifacts(compile_target, &changed_artifacts);
+            }
+            Err(error) => messages::error(&error, Some(&compile_target.target_dir)),
+        },
+        |error| messages::error(error, Some(&compile_target.target_dir)),
+    )
+}
+
+/// Reruns the compile subcommand whenever its Sierra input changes, until interrupted.
+fn watch_sierra_file(command: &Commands, sierra_path: &Path) -> ! {
+    watch::watch(
+        || Ok(vec![sierra_path.to_path_buf()]),
+        |_| {
+            let start = Instant::now();
+            match run_command(command.clone()) {
+                Ok(_) => messages::watch(&format!(
+                    "Compiled {} in {:.2?}",
+                    sierra_path.display(),
+                    start.elapsed()
+                )),
+                Err(error) => messages::error(&error, Some(sierra_path)),
+            }
+        },
+        |error| messages::error(error, Some(sierra_path)),
+    )
+}
+
+/// Points to the right subcommand if compilation failed because a Sierra program was passed to
+/// `compile-contract`, or a contract class to `compile-raw`.
+fn suggest_subcommand(error: Error, sierra_path: &Path, expected_kind: SierraKind) -> Error {
+    let Ok(sierra_content) = std::fs::read(sierra_path) else {
+        return error;
+    };
+    // Any input that is not JSON is detected as textual Sierra, which is no hint of a mix-up.
+    if commands::compile_raw::is_program_text(&sierra_content) {
+        return error;
+    }
+
+    let suggestion = match (SierraKind::detect(&sierra_content), expected_kind) {
+        (Some(SierraKind::Raw), SierraKind::Contract) => {
+            "The input is a Sierra program rather than a contract class, compile it with `compile-raw` or `compile`"
+        }
+        (Some(SierraKind::Contract), SierraKind::Raw) => {
+            "The input is a contract class rather than a Sierra program, compile it with `compile-contract` or `compile`"
+        }
+        _ => return error,
+    };
+    note::with_note(error, suggestion)
+}
+
+fn main_execution(cli: Cli) -> Result<bool> {
+    let _g = init_logging();
+
+    match &cli.command {
+        Commands::Compile(compile) if compile.watch => {
+            watch_sierra_file(&cli.command, &compile.sierra_path)
+        }
+        Commands::CompileContract(compile_contract) if compile_contract.watch => {
+            watch_sierra_file(&cli.command, &compile_contract.sierra_path)
+        }
+        Commands::CompileRaw(compile_raw) if compile_raw.watch => {
+            watch_sierra_file(&cli.command, &compile_raw.sierra_path)
+        }
+        Commands::CompileTarget(compile_target) if compile_target.watch => {
+            watch_target_dir(compile_target)
+        }
+        _ => run_command(cli.command),
+    }
+}
+
+/// Returns the input the command reads, which diagnostics about it refer to.
+fn input_path(command: &Commands) -> Option<&Path> {
+    match command {
+        Commands::Compile(compile) => Some(&compile.sierra_path),
+        Commands::CompileContract(compile_contract) => Some(&compile_contract.sierra_path),
+        Commands::CompileRaw(compile_raw) => Some(&compile_raw.sierra_path),
+        Commands::CompileTarget(compile_target) => Some(&compile_target.target_dir),
+        Commands::Convert(convert) => Some(&convert.sierra_path),
+        Commands::Verify(verify) => Some(&verify.sierra_path),
+        Commands::Diff(_) | Commands::SupportedVersions => None,
+    }
+}
+
+/// Executes the command, pointing to the right subcommand if it was given the wrong kind of input,
+/// and reporting how long compiling a single input took.
+fn run_command(command: Commands) -> Result<bool> {
+    let expected_input = match &command {
+        Commands::CompileContract(compile_contract) => {
+            Some((compile_contract.sierra_path.clone(), SierraKind::Contract))
+        }
+        Commands::CompileRaw(compile_raw) => {
+            Some((compile_raw.sierra_path.clone(), SierraKind::Raw))
+        }
+        _ => None,
+    };
+    let command_input = input_path(&command).map(Path::to_path_buf);
+    let compiled_input = matches!(
+        command,
+        Commands::Compile(_) | Commands::CompileContract(_) | Commands::CompileRaw(_)
+    )
+    .then(|| command_input.clone())
+    .flatten();
+    let start = Instant::now();
+
+    let result = execute_command(command).map_err(|error| {
+        let error = match &command_input {
+            Some(input_path) => locate_json_error(error, input_path),
+            None => error,
+        };
+        match expected_input {
+            Some((sierra_path, expected_kind)) => {
+                suggest_subcommand(error, &sierra_path, expected_kind)
+            }
+            None => error,
+        }
+    });
+    if let (Ok(_), Some(compiled_input)) = (&result, compiled_input) {
+        messages::timing(&compiled_input, start.elapsed());
+    }
+    result
+}
+
+/// Adds the line and column of the failing field to errors of deserializing JSON values read from
+/// the input, which only know the path of the field.
+fn locate_json_error(error: Error, input_path: &Path) -> Error {
+    let Some(located) = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<JsonError>())
+        .and_then(|json_error| json_error.located(&std::fs::read(input_path).ok()?))
+    else {
+        return error;
+    };
+    error.context(located)
+}
+
+fn execute_command(command: Commands) -> Result<bool> {
+    match command {
+        Commands::Compile(compile) => {
+            return execute_command(resolve_compile_command(compile)?);
+        }
+        Commands::CompileContract(compile_contract) if compile_contract.emit == Emit::CasmText => {
+            let sierra_json = deserialize_json(&read_sierra_file(&compile_contract.sierra_path)?)?;
+            let compiled_contract = compile_contract::compile_program(sierra_json)?;
+            let casm_text = casm_text::from_compiled(&compiled_contract);
+            check_allowed_libfuncs(&compile_contract, None)?;
+
+            output_casm_text(&casm_text, compile_contract.output_path.clone())?;
+            output_compiled_contract_sidecars(&compile_contract, &compiled_contract)?;
+        }
+        Commands::CompileContract(compile_contract)
+            if matches!(
+                compile_contract.emit,
+                Emit::SizeReport | Emit::SizeReportJson
+            ) =>
+        {
+            let sierra_json = deserialize_json(&read_sierra_file(&compile_contract.sierra_path)?)?;
+            let compiled_contract = compile_contract::compile_program(sierra_json)?;
+            let size_report = size_report::from_compiled(&compiled_contract);
+            check_allowed_libfuncs(&compile_contract, None)?;
+
+            output_size_report(
+                &size_report,
+                compile_contract.emit,
+                compile_contract.output_path.clone(),
             )?;
+            output_compiled_contract_sidecars(&compile_contract, &compiled_contract)?;
+        }
+        Commands::CompileContract(compile_contract) => {
+            let compiled_class = contract_sidecars_requested(&compile_contract)
+                .then(|| compile_contract_class(&compile_contract))
+                .transpose()?;
+            let route = unsupported_version_route(&compile_contract)?;
+            let casm_json = match &compiled_class {
+                Some(compiled_class) => serde_json::to_value(&compiled_class.casm_class)?,
+                None => compile_contract_with_cache(&compile_contract, route.as_ref())?,
+            };
+            check_allowed_libfuncs(&compile_contract, route.as_ref())?;
+
+            output_casm(&casm_json, compile_contract.output_path.clone())?;
+            if let Some(compiled_class) = &compiled_class {
+                output_contract_sidecars(
+                    &compile_contract,
+                    &compiled_class.entry_points_by_type,
+                    &compiled_class.program,
+                    &compiled_class.statement_pc_ranges,
+                    || compiled_class.metadata_without_entry_point_costs(),
+                )?;
+            }
+        }
+        Commands::CompileRaw(compile_raw)
+            if compile_raw.sierra_version.is_some() && compile_raw.emit != Emit::Json =>
+        {
+            return Err(
+                ErrorKind::Usage.error("`--sierra-version` is only supported with `--emit json`")
+            );
+        }
+        Commands::CompileRaw(compile_raw) if compile_raw.emit == Emit::CasmText => {
+            let sierra_program =
+                deserialize_sierra_program(&read_sierra_file(&compile_raw.sierra_path)?)?;
+            let casm_text = casm_text::compile_raw(&sierra_program)?;
 
-            output_casm(&casm_json, compile_contract.output_path)?;
+            output_casm_text(&casm_text, compile_raw.output_path)?;
+        }
+        Commands::CompileRaw(compile_raw)
+            if matches!(compile_raw.emit, Emit::SizeReport | Emit::SizeReportJson) =>
+        {
+            let sierra_program =
+                deserialize_sierra_program(&read_sierra_file(&compile_raw.sierra_path)?)?;
+            let size_report = size_report::compile_raw(&sierra_program)?;
+
+            output_size_report(&size_report, compile_raw.emit, compile_raw.output_path)?;
         }
         Commands::CompileRaw(compile_raw) => {
             let sierra_path = compile_raw.sierra_path;
-            let cairo_program_json = cache::compile_with_cache(
+            let options = CompileRawOptions {
+                statement_metadata: compile_raw.statement_metadata,
+            };
+            let sierra_version = compile_raw.sierra_version;
+            // Programs compiled with other options or by other compilers are cached separately.
+            let cache_variant = [
+                options
+                    .statement_metadata
+                    .then(|| "statement-metadata".to_string()),
+                sierra_version
+                    .as_ref()
+                    .map(|sierra_version| format!("sierra-version:{sierra_version}")),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(",");
+            let cairo_program_json = cache::compile_variant_with_cache(
                 &sierra_path,
                 SierraKind::Raw,
+                &cache_variant,
                 compile_raw.cache_dir.as_deref(),
-                |sierra_content| {
-                    let sierra_program: Program = deserialize_json(sierra_content).context(
-                        "Unable to deserialize Sierra program. Make sure it is in a correct format",
-                    )?;
-                    commands::compile_raw::compile(&sierra_program)
+                |sierra_content| match &sierra_version {
+                    Some(sierra_version) => {
+                        let sierra_program =
+                            if commands::compile_raw::is_program_text(sierra_content) {
+                                serde_json::to_value(deserialize_sierra_program(sierra_content)?)?
+                            } else {
+                                json::from_slice(sierra_content, "Sierra program")?
+                            };
+                        commands::compile_raw::compile_with_registry(
+                            sierra_program,
+                            sierra_version,
+                            &options,
+                            &BackendRegistry::default(),
+                        )
+                    }
+                    None => {
+                        let sierra_program = deserialize_sierra_program(sierra_content)?;
+                        commands::compile_raw::compile(&sierra_program, &options)
+                    }
                 },
             )?;
 
             output_casm(&cairo_program_json, compile_raw.output_path)?;
         }
+        Commands::CompileTarget(compile_target) => {
+            return compile_target_dir(&compile_target);
+        }
+        Commands::Diff(diff) => {
+            let old_json = read_diff_input(&diff.old_path)?;
+            let new_json = read_diff_input(&diff.new_path)?;
+            let thresholds = DiffThresholds {
+                max_bytecode_length_increase: diff.max_bytecode_length_increase,
+                max_gas_increase: diff.max_gas_increase,
+            };
+            let report = commands::diff::diff(old_json, new_json, &thresholds)?;
+
+            if let Some(output_path) = &diff.output_path {
+                output_json_file(&report.output, output_path, "diff")?;
+            } else {
+                output_casm(&report.output, None)?;
+            }
+
+            for exceeded_threshold in &report.exceeded_thresholds {
+                messages::error(&anyhow!("{exceeded_threshold}"), None);
+            }
+            return Ok(report.exceeded_thresholds.is_empty());
+        }
+        Commands::Convert(convert) => {
+            let converted = commands::convert::convert(
+                &read_sierra_file(&convert.sierra_path)?,
+                convert.to,
+                convert.sierra_version.as_deref(),
+                &BackendRegistry::default(),
+            )?;
+
+            match converted {
+                Converted::Json(program) => output_casm(&program, convert.output_path)?,
+                Converted::Text(program) => output_casm_text(&program, convert.output_path)?,
+            }
+        }
+        Commands::SupportedVersions => {
+            output_casm(&delegation::supported_versions(), None)?;
+        }
+        Commands::Verify(verify) => {
+            let sierra_json = deserialize_json(&read_sierra_file(&verify.sierra_path)?)?;
+            let casm_content = std::fs::read(&verify.casm_path).with_context(|| {
+                format!(
+                    "Unable to read CASM input file: {}",
+                    verify.casm_path.display()
+                )
+            })?;
+            let casm_json = deserialize_json(&casm_content)?;
+
+            if let Some(mismatch) = commands::verify::verify(sierra_json, casm_json)? {
+                messages::error(
+                    &anyhow!("CASM does not match the compiled Sierra. {mismatch}"),
+                    Some(&verify.sierra_path),
+                );
+                return Ok(false);
+            }
+            println!("CASM matches the compiled Sierra");
+        }
     }
 
     Ok(true)
 }
 
+/// Exit code of commands that ran, but whose check failed: `verify` found a mismatch, `diff`
+/// exceeded a threshold, or `compile-target` didn't compile some of the artifacts.
+const CHECK_FAILED_EXIT_CODE: i32 = 1;
+
+/// Returns the exit code of failures of the kind. Usage errors exit with 2, like those of clap.
+fn exit_code(error_kind: ErrorKind) -> i32 {
+    match error_kind {
+        ErrorKind::Usage => 2,
+        ErrorKind::InvalidInput => 3,
+        ErrorKind::UnsupportedVersion => 4,
+        ErrorKind::Compilation => 5,
+        ErrorKind::Io => 6,
+    }
+}
+
+/// Returns the message format requested in `args`, for reporting the errors of parsing them.
+fn raw_message_format(args: impl IntoIterator<Item = OsString>) -> MessageFormat {
+    let mut message_format = MessageFormat::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.to_str() {
+            Some("--message-format") => args.next(),
+            Some(arg) => arg.strip_prefix("--message-format=").map(OsString::from),
+            None => None,
+        };
+        if let Some(format) = value
+            .as_deref()
+            .and_then(OsStr::to_str)
+            .and_then(|value| MessageFormat::from_str(value, false).ok())
+        {
+            message_format = format;
+        }
+    }
+    message_format
+}
+
 fn main() {
-    match main_execution() {
+    // Panics of backends are reported as errors, which the default hook would precede.
+    backend::install_quiet_panic_hook();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Help and version are printed to stdout and exit successfully.
+        Err(error) if !error.use_stderr() => error.exit(),
+        Err(error) => {
+            messages::set_format(raw_message_format(std::env::args_os()));
+            let message = error.render().to_string();
+            let message = message
+                .strip_prefix("error: ")
+                .unwrap_or(&message)
+                .trim_end();
+            messages::error(&ErrorKind::Usage.error(message), None);
+            std::process::exit(exit_code(ErrorKind::Usage));
+        }
+    };
+    messages::set_format(cli.message_format);
+    let input_path = input_path(&cli.command).map(Path::to_path_buf);
+
+    match main_execution(cli) {
         Ok(true) => std::process::exit(0),
-        Ok(false) => std::process::exit(1),
+        Ok(false) => std::process::exit(CHECK_FAILED_EXIT_CODE),
         Err(error) => {
-            print_error_message(&error);
-            std::process::exit(2);
+            messages::error(&error, input_path.as_deref());
+            let code = delegation::delegate_exit_code(&error)
+                .unwrap_or_else(|| exit_code(ErrorKind::of(&error)));
+            std::process::exit(code);
         }
     };
 }
@@ -0,0 +1,148 @@
+//! Diagnostics printed to stderr, either as coloured lines or as newline-delimited JSON objects.
+//!
+//! Every JSON object has the `kind`, `message`, `input_path` and `causes` fields. Errors list
+//! their cause chain in `causes`, outermost first, other kinds leave it empty. Notes added to an
+//! error are printed after its message, as in the human format.
+
+use crate::note::Noted;
+use anyhow::Error;
+use clap::ValueEnum;
+use console::style;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Format of the diagnostics.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum MessageFormat {
+    /// Coloured `[ERROR]` and `[WARNING]` lines.
+    #[default]
+    Human,
+    /// Newline-delimited JSON objects, including cache events and timing.
+    Json,
+}
+
+static MESSAGE_FORMAT: OnceLock<MessageFormat> = OnceLock::new();
+
+/// Sets the format of all diagnostics printed afterwards. Only the first call has an effect.
+pub fn set_format(format: MessageFormat) {
+    MESSAGE_FORMAT.get_or_init(|| format);
+}
+
+fn message_format() -> MessageFormat {
+    MESSAGE_FORMAT.get().copied().unwrap_or_default()
+}
+
+/// Outcome of a CASM cache lookup.
+#[derive(Clone, Copy, Debug)]
+pub enum CacheEvent {
+    Hit,
+    Miss,
+}
+
+impl CacheEvent {
+    fn kind(self) -> &'static str {
+        match self {
+            Self::Hit => "cache-hit",
+            Self::Miss => "cache-miss",
+        }
+    }
+}
+
+fn print_json(kind: &str, message: &str, input_path: Option<&Path>, causes: &[String]) {
+    print_json_value(&json_message(kind, message, input_path, causes));
+}
+
+fn print_json_value(message: &Value) {
+    eprintln!("{message}");
+}
+
+fn json_message(kind: &str, message: &str, input_path: Option<&Path>, causes: &[String]) -> Value {
+    json!({
+        "kind": kind,
+        "message": message,
+        "input_path": input_path.map(|input_path| input_path.display().to_string()),
+        "causes": causes,
+    })
+}
+
+/// Prints the error with the notes added to it after its message. Notes and the message are left
+/// out of the causes.
+pub fn error(error: &Error, input_path: Option<&Path>) {
+    let notes: Vec<String> = error
+        .chain()
+        .map_while(|cause| cause.downcast_ref::<Noted>())
+        .map(ToString::to_string)
+        .collect();
+    let mut chain = error.chain().skip(notes.len());
+    let mut message = chain.next().map(ToString::to_string).unwrap_or_default();
+    for note in notes.iter().rev() {
+        message.push('\n');
+        message.push_str(note);
+    }
+
+    match message_format() {
+        MessageFormat::Human => {
+            let error_tag = style("ERROR").red();
+            eprintln!("[{error_tag}] {message}");
+        }
+        MessageFormat::Json => {
+            let causes: Vec<_> = chain.map(ToString::to_string).collect();
+            print_json("error", &message, input_path, &causes);
+        }
+    }
+}
+
+pub fn warning(message: &str, input_path: Option<&Path>) {
+    match message_format() {
+        MessageFormat::Human => {
+            let warning_tag = style("WARNING").yellow();
+            eprintln!("[{warning_tag}] {message}");
+        }
+        MessageFormat::Json => print_json("warning", message, input_path, &[]),
+    }
+}
+
+/// Prints the result of a rebuild in watch mode. JSON diagnostics report every compilation with
+/// [`timing`] instead.
+pub fn watch(message: &str) {
+    if message_format() == MessageFormat::Human {
+        let watch_tag = style("WATCH").cyan();
+        eprintln!("[{watch_tag}] {message}");
+    }
+}
+
+/// Prints the summary of compiling multiple inputs, to stdout for humans.
+pub fn summary(message: &str) {
+    match message_format() {
+        MessageFormat::Human => println!("{message}"),
+        MessageFormat::Json => print_json("summary", message, None, &[]),
+    }
+}
+
+/// Reports a CASM cache lookup. Only JSON diagnostics include cache events.
+pub fn cache_event(event: CacheEvent, input_path: &Path) {
+    if message_format() == MessageFormat::Json {
+        let message = match event {
+            CacheEvent::Hit => "Loaded CASM from the cache",
+            CacheEvent::Miss => "CASM is not cached, compiling",
+        };
+        print_json(event.kind(), message, Some(input_path), &[]);
+    }
+}
+
+/// Reports how long compiling the input took. Only JSON diagnostics include timing, with the
+/// duration in the `elapsed_ms` field.
+pub fn timing(input_path: &Path, elapsed: Duration) {
+    if message_format() == MessageFormat::Json {
+        let mut message = json_message(
+            "timing",
+            &format!("Compiled {} in {elapsed:.2?}", input_path.display()),
+            Some(input_path),
+            &[],
+        );
+        message["elapsed_ms"] = json!(elapsed.as_millis());
+        print_json_value(&message);
+    }
+}
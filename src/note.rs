@@ -0,0 +1,42 @@
+//! Notes added to errors, such as where they occurred or how to fix them.
+//!
+//! Unlike the context of an error, a note doesn't replace its message. Diagnostics print the notes
+//! on their own lines after the message, which stays the first cause of the error, so that neither
+//! of them is repeated along the cause chain.
+
+use std::error::Error;
+use std::fmt;
+
+/// Error with a note printed after its message.
+#[derive(Debug)]
+pub struct Noted {
+    note: String,
+    error: anyhow::Error,
+}
+
+impl Noted {
+    /// Returns the error the note was added to.
+    pub fn error(&self) -> &anyhow::Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for Noted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.note)
+    }
+}
+
+impl Error for Noted {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&**self.error)
+    }
+}
+
+/// Adds `note` to `error`.
+pub fn with_note(error: impl Into<anyhow::Error>, note: impl fmt::Display) -> anyhow::Error {
+    anyhow::Error::new(Noted {
+        note: note.to_string(),
+        error: error.into(),
+    })
+}
@@ -0,0 +1,183 @@
+//! Breakdown of the compiled bytecode length by function and by libfunc.
+
+use crate::backend::catch_latest_backend_panic;
+use crate::commands::compile_contract::{self, CompiledContractProgram};
+use crate::commands::compile_raw::{self, function_statement_ranges, CompiledProgram};
+use anyhow::Result;
+use cairo_lang_sierra::program::{Program, Statement};
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Name under which the bytecode of `return` statements is reported among libfuncs.
+const RETURN_STATEMENT_NAME: &str = "return";
+
+/// Bytecode length (in felts) attributed to a single function or libfunc.
+#[derive(Clone, Debug)]
+pub struct SizeEntry {
+    pub name: String,
+    /// Number of Sierra statements attributed to this entry.
+    pub statements: usize,
+    pub bytecode_length: usize,
+}
+
+/// Bytecode length of a compiled program, attributed to the Sierra code that generated it.
+/// Entries are sorted by bytecode length, largest first.
+#[derive(Clone, Debug)]
+pub struct SizeReport {
+    /// Length of the whole assembled bytecode, including the constants.
+    pub bytecode_length: usize,
+    /// Length of the constant segments following the code, which no Sierra statement generates.
+    pub consts_length: usize,
+    pub functions: Vec<SizeEntry>,
+    pub libfuncs: Vec<SizeEntry>,
+}
+
+/// Compiles Sierra of the plain Cairo code and reports what its bytecode length consists of.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile_raw(sierra_program: &Program) -> Result<SizeReport> {
+    let compiled = catch_latest_backend_panic(|| {
+        compile_raw::compile_program(sierra_program, MetadataComputationConfig::default())
+    })?;
+
+    Ok(SizeReport::new(sierra_program, &compiled))
+}
+
+/// Compiles Sierra of the Starknet contract and reports what its bytecode length consists of.
+#[tracing::instrument(skip_all, level = "info")]
+pub fn compile_contract(sierra_json: Value) -> Result<SizeReport> {
+    Ok(from_compiled(&compile_contract::compile_program(
+        sierra_json,
+    )?))
+}
+
+/// Reports what the bytecode length of the already compiled contract consists of.
+pub(crate) fn from_compiled(compiled_contract: &CompiledContractProgram) -> SizeReport {
+    SizeReport::new(&compiled_contract.program, &compiled_contract.compiled)
+}
+
+impl SizeReport {
+    fn new(sierra_program: &Program, compiled: &CompiledProgram) -> Self {
+        let statement_pc_ranges = compiled.statement_pc_ranges();
+        let code_size = statement_pc_ranges.last().map_or(0, |range| range.end);
+        let bytecode_length = compiled.cairo_program.assemble().bytecode.len();
+        let statements_length = |statements: std::ops::Range<usize>| {
+            let start = statement_pc_ranges[statements.start].start;
+            let end = statement_pc_ranges
+                .get(statements.end)
+                .map_or(code_size, |range| range.start);
+            end - start
+        };
+
+        let functions = sierra_program
+            .funcs
+            .iter()
+            .zip(function_statement_ranges(sierra_program))
+            .map(|(function, statements)| SizeEntry {
+                name: function.id.to_string(),
+                statements: statements.len(),
+                bytecode_length: statements_length(statements),
+            })
+            .collect();
+
+        let libfunc_names: HashMap<_, _> = sierra_program
+            .libfunc_declarations
+            .iter()
+            .map(|declaration| (&declaration.id, declaration.long_id.generic_id.to_string()))
+            .collect();
+        let mut libfuncs: HashMap<String, SizeEntry> = HashMap::new();
+        for (statement_idx, statement) in sierra_program.statements.iter().enumerate() {
+            let name = match statement {
+                Statement::Invocation(invocation) => libfunc_names
+                    .get(&invocation.libfunc_id)
+                    .cloned()
+                    .unwrap_or_else(|| invocation.libfunc_id.to_string()),
+                Statement::Return(_) => RETURN_STATEMENT_NAME.to_string(),
+            };
+            let entry = libfuncs.entry(name.clone()).or_insert(SizeEntry {
+                name,
+                statements: 0,
+                bytecode_length: 0,
+            });
+            entry.statements += 1;
+            entry.bytecode_length += statement_pc_ranges[statement_idx].len();
+        }
+
+        Self {
+            bytecode_length,
+            consts_length: bytecode_length - code_size,
+            functions: sorted_by_length(functions),
+            libfuncs: sorted_by_length(libfuncs.into_values().collect()),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let serialize_entries = |entries: &[SizeEntry]| -> Vec<Value> {
+            entries
+                .iter()
+                .map(|entry| {
+                    json!({
+                        "name": entry.name,
+                        "statements": entry.statements,
+                        "bytecode_length": entry.bytecode_length,
+                    })
+                })
+                .collect()
+        };
+
+        json!({
+            "bytecode_length": self.bytecode_length,
+            "consts_length": self.consts_length,
+            "functions": serialize_entries(&self.functions),
+            "libfuncs": serialize_entries(&self.libfuncs),
+        })
+    }
+
+    /// Renders the report as plain text tables, with each entry's share of the total length.
+    pub fn to_table(&self) -> String {
+        let mut table = String::new();
+        writeln!(table, "Bytecode length: {}", self.bytecode_length).unwrap();
+        writeln!(table, "Constants length: {}", self.consts_length).unwrap();
+        for (title, entries) in [("Function", &self.functions), ("Libfunc", &self.libfuncs)] {
+            let name_width = entries
+                .iter()
+                .map(|entry| entry.name.len())
+                .chain([title.len()])
+                .max()
+                .unwrap_or_default();
+
+            writeln!(table).unwrap();
+            writeln!(
+                table,
+                "{title:<name_width$}  {:>10}  {:>6}  {:>7}",
+                "Statements", "Length", "Share"
+            )
+            .unwrap();
+            for entry in entries {
+                let share = if self.bytecode_length == 0 {
+                    0.0
+                } else {
+                    entry.bytecode_length as f64 * 100.0 / self.bytecode_length as f64
+                };
+                writeln!(
+                    table,
+                    "{:<name_width$}  {:>10}  {:>6}  {:>6.2}%",
+                    entry.name, entry.statements, entry.bytecode_length, share
+                )
+                .unwrap();
+            }
+        }
+
+        table
+    }
+}
+
+fn sorted_by_length(mut entries: Vec<SizeEntry>) -> Vec<SizeEntry> {
+    entries.sort_by(|a, b| {
+        b.bytecode_length
+            .cmp(&a.bytecode_length)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    entries
+}
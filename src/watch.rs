@@ -0,0 +1,123 @@
+//! Polling watcher rerunning compilation when its Sierra inputs change.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Modification times of the watched inputs, telling which of them changed since the last poll.
+#[derive(Default)]
+struct Snapshot(HashMap<PathBuf, SystemTime>);
+
+impl Snapshot {
+    /// Records the modification times of `paths`, returning the ones that are new or modified.
+    /// Inputs that can't be read at the moment, e.g. while being rewritten, are skipped until
+    /// they reappear.
+    fn update(&mut self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut modification_times = HashMap::with_capacity(paths.len());
+        let mut changed = vec![];
+        for path in paths {
+            let Ok(modified) = std::fs::metadata(&path).and_then(|metadata| metadata.modified())
+            else {
+                continue;
+            };
+            if self.0.get(&path) != Some(&modified) {
+                changed.push(path.clone());
+            }
+            modification_times.insert(path, modified);
+        }
+
+        self.0 = modification_times;
+        changed
+    }
+}
+
+/// Calls `rebuild` with all inputs returned by `find_inputs`, and then, until the process is
+/// interrupted, with the ones that changed since. Inputs are found again on every poll, so that
+/// added files are picked up. Errors of `find_inputs` are passed to `report_error`, once until
+/// they change.
+pub fn watch(
+    mut find_inputs: impl FnMut() -> Result<Vec<PathBuf>>,
+    mut rebuild: impl FnMut(&[PathBuf]),
+    mut report_error: impl FnMut(&anyhow::Error),
+) -> ! {
+    let mut snapshot = Snapshot::default();
+    let mut last_error = None;
+
+    loop {
+        match find_inputs() {
+            Ok(inputs) => {
+                last_error = None;
+                let changed = snapshot.update(inputs);
+                if !changed.is_empty() {
+                    rebuild(&changed);
+                }
+            }
+            Err(error) => {
+                let message = format!("{error:#}");
+                if last_error.as_ref() != Some(&message) {
+                    report_error(&error);
+                    last_error = Some(message);
+                }
+            }
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+
+    fn touch(path: &PathBuf, modified: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn reports_new_and_modified_inputs() {
+        let temp = tempfile::tempdir().unwrap();
+        let first = temp.path().join("first.sierra.json");
+        let second = temp.path().join("second.sierra.json");
+        fs::write(&first, b"{}").unwrap();
+        fs::write(&second, b"{}").unwrap();
+        let mut snapshot = Snapshot::default();
+
+        assert_eq!(
+            snapshot.update(vec![first.clone(), second.clone()]),
+            vec![first.clone(), second.clone()]
+        );
+        assert!(snapshot
+            .update(vec![first.clone(), second.clone()])
+            .is_empty());
+
+        touch(&second, SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        assert_eq!(
+            snapshot.update(vec![first.clone(), second.clone()]),
+            vec![second]
+        );
+    }
+
+    #[test]
+    fn skips_missing_inputs_until_they_reappear() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("program.sierra.json");
+        fs::write(&path, b"{}").unwrap();
+        let mut snapshot = Snapshot::default();
+        snapshot.update(vec![path.clone()]);
+
+        fs::remove_file(&path).unwrap();
+        assert!(snapshot.update(vec![path.clone()]).is_empty());
+
+        fs::write(&path, b"{}").unwrap();
+        assert_eq!(snapshot.update(vec![path.clone()]), vec![path]);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::e2e::{runner, temp_dir_with_sierra_file};
+use indoc::indoc;
+use serde_json::Value;
+use std::fs::{self, File};
+use test_case::test_case;
+
+fn read_output(temp_dir: &tempfile::TempDir) -> Value {
+    let file = File::open(temp_dir.path().join("casm.json")).unwrap();
+    serde_json::from_reader(file).unwrap()
+}
+
+#[test_case("sierra_contract", "sierra_1_9_0.json", "bytecode"; "contract")]
+#[test_case("sierra_raw", "sierra_1_9_0.json", "assembled_cairo_program"; "versioned program")]
+#[test_case("sierra_raw", "add.sierra", "assembled_cairo_program"; "sierra text")]
+fn detects_input_kind(dir_name: &str, sierra_file_name: &str, output_field: &str) {
+    let args = vec![
+        "compile",
+        "--sierra-path",
+        sierra_file_name,
+        "--output-path",
+        "casm.json",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file(dir_name, sierra_file_name);
+    runner(args, &temp_dir).assert().success();
+
+    assert!(read_output(&temp_dir).get(output_field).is_some());
+}
+
+#[test]
+fn unrecognized_input() {
+    let sierra_file_name = "unknown.json";
+    let args = vec!["compile", "--sierra-path", sierra_file_name];
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    fs::write(temp_dir.path().join(sierra_file_name), r#"{"unknown": []}"#).unwrap();
+
+    runner(args, &temp_dir).assert().code(3).stderr_eq(indoc! {r"
+        [ERROR] Unable to detect the kind of Sierra input. Expected a contract class with `sierra_program` field, or a Sierra program in json or textual form
+    "});
+}
+
+#[test]
+fn program_passed_to_compile_contract() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec!["compile-contract", "--sierra-path", sierra_file_name];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+
+    runner(args, &temp_dir).assert().code(3).stderr_eq(indoc! {r"
+        [ERROR] [..]
+        The input is a Sierra program rather than a contract class, compile it with `compile-raw` or `compile`
+    "});
+}
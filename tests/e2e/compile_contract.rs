@@ -3,6 +3,7 @@ use crate::e2e::{
 };
 use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
 use indoc::indoc;
+use serde_json::Value;
 use std::fs::{self, File};
 use std::path::PathBuf;
 use test_case::test_case;
@@ -212,6 +213,563 @@ fn changed_sierra_invalidates_cache() {
     assert!(cached_casm_file(&temp_dir.path().join(cache_dir_name)).is_file());
 }
 
+#[test]
+fn emit_casm_text() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        &sierra_file_name,
+        "--emit",
+        "casm-text",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    let output = String::from_utf8(snapbox.assert().success().get_output().stdout.clone()).unwrap();
+    assert!(output.contains("// EXTERNAL entry point, selector 0x"));
+    assert!(output.contains("// #0: "));
+    assert!(output.contains("ret;"));
+}
+
+#[test]
+fn emit_casm_text_for_legacy_sierra() {
+    let sierra_file_name = "sierra_1_0_0.json";
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        &sierra_file_name,
+        "--emit",
+        "casm-text",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    snapbox.assert().code(4).stderr_eq(indoc! {r"
+        [ERROR] Unable to analyze compiled contract. It is only supported for Sierra 1.2.0 and newer, as older compilers don't expose the CASM of each Sierra statement, found version 1.0.0
+    "});
+}
+
+#[test]
+fn debug_info_for_legacy_sierra() {
+    let sierra_file_name = "sierra_1_1_0.json";
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        &sierra_file_name,
+        "--debug-info-path",
+        "debug_info.json",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    snapbox.assert().code(4).stderr_eq(indoc! {r"
+        [ERROR] `--debug-info-path` and `--entry-point-costs-path` are unsupported for Sierra version 1.1.0. They are only supported for Sierra 1.2.0 and newer, as older compilers don't expose the CASM of each Sierra statement
+    "});
+    assert!(!temp_dir.path().join("debug_info.json").exists());
+}
+
+#[test]
+fn emit_size_report_json() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let casm_file_name = "casm.json";
+    let size_report_file_name = "size_report.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+
+    runner(
+        vec![
+            "compile-contract",
+            "--sierra-path",
+            sierra_file_name,
+            "--output-path",
+            casm_file_name,
+        ],
+        &temp_dir,
+    )
+    .assert()
+    .success();
+    runner(
+        vec![
+            "compile-contract",
+            "--sierra-path",
+            sierra_file_name,
+            "--output-path",
+            size_report_file_name,
+            "--emit",
+            "size-report-json",
+        ],
+        &temp_dir,
+    )
+    .assert()
+    .success();
+
+    let casm_class: CasmContractClass =
+        serde_json::from_reader(File::open(temp_dir.path().join(casm_file_name)).unwrap()).unwrap();
+    let size_report: Value =
+        serde_json::from_reader(File::open(temp_dir.path().join(size_report_file_name)).unwrap())
+            .unwrap();
+
+    let bytecode_length = size_report["bytecode_length"].as_u64().unwrap();
+    assert_eq!(bytecode_length, casm_class.bytecode.len() as u64);
+    // Constant segments following the code are not attributed to any function or libfunc.
+    let consts_length = size_report["consts_length"].as_u64().unwrap();
+    for section in ["functions", "libfuncs"] {
+        let entries = size_report[section].as_array().unwrap();
+        assert!(!entries.is_empty());
+        let total: u64 = entries
+            .iter()
+            .map(|entry| entry["bytecode_length"].as_u64().unwrap())
+            .sum();
+        assert_eq!(
+            total + consts_length,
+            bytecode_length,
+            "{section} should cover the whole code"
+        );
+    }
+}
+
+#[test]
+fn write_debug_info() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let casm_file_name = "casm.json";
+    let debug_info_file_name = "debug_info.json";
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        &sierra_file_name,
+        "--output-path",
+        casm_file_name,
+        "--debug-info-path",
+        debug_info_file_name,
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    runner(args, &temp_dir).assert().success();
+
+    let casm_class: CasmContractClass =
+        serde_json::from_reader(File::open(temp_dir.path().join(casm_file_name)).unwrap()).unwrap();
+    let debug_info: Value =
+        serde_json::from_reader(File::open(temp_dir.path().join(debug_info_file_name)).unwrap())
+            .unwrap();
+
+    let pc_ranges = serde_json::from_value::<Vec<(usize, usize)>>(
+        debug_info["sierra_statement_pc_ranges"].clone(),
+    )
+    .unwrap();
+    assert!(!pc_ranges.is_empty());
+    assert_eq!(pc_ranges[0].0, 0);
+    assert!(pc_ranges
+        .windows(2)
+        .all(|ranges| ranges[0].0 <= ranges[0].1 && ranges[0].1 == ranges[1].0));
+
+    let external = debug_info["entry_points_by_type"]["EXTERNAL"]
+        .as_array()
+        .unwrap();
+    assert_eq!(
+        external.len(),
+        casm_class.entry_points_by_type.external.len()
+    );
+    for (entry_point, casm_entry_point) in external
+        .iter()
+        .zip(&casm_class.entry_points_by_type.external)
+    {
+        assert_eq!(
+            entry_point["offset"].as_u64().unwrap(),
+            casm_entry_point.offset as u64
+        );
+        assert_eq!(
+            entry_point["selector"].as_str().unwrap(),
+            format!("{:#x}", casm_entry_point.selector)
+        );
+        assert!(entry_point["function_idx"].is_u64());
+        assert!(entry_point["statement_idx"].is_u64());
+    }
+}
+
+#[test]
+fn write_entry_point_costs() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let casm_file_name = "casm.json";
+    let entry_point_costs_file_name = "entry_point_costs.json";
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        &sierra_file_name,
+        "--output-path",
+        casm_file_name,
+        "--entry-point-costs-path",
+        entry_point_costs_file_name,
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    runner(args, &temp_dir).assert().success();
+
+    let casm_class: CasmContractClass =
+        serde_json::from_reader(File::open(temp_dir.path().join(casm_file_name)).unwrap()).unwrap();
+    let entry_point_costs: Value = serde_json::from_reader(
+        File::open(temp_dir.path().join(entry_point_costs_file_name)).unwrap(),
+    )
+    .unwrap();
+
+    let external = entry_point_costs["entry_points_by_type"]["EXTERNAL"]
+        .as_array()
+        .unwrap();
+    assert_eq!(
+        external.len(),
+        casm_class.entry_points_by_type.external.len()
+    );
+    for (entry_point, casm_entry_point) in external
+        .iter()
+        .zip(&casm_class.entry_points_by_type.external)
+    {
+        assert_eq!(
+            entry_point["selector"].as_str().unwrap(),
+            format!("{:#x}", casm_entry_point.selector)
+        );
+        assert_eq!(
+            entry_point["offset"].as_u64().unwrap(),
+            casm_entry_point.offset as u64
+        );
+        assert!(entry_point["bytecode_length"].as_u64().unwrap() > 0);
+        assert!(entry_point["costs"]["Const"].as_i64().unwrap() > 0);
+    }
+    assert!(entry_point_costs["entry_points_by_type"]["CONSTRUCTOR"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn allowed_libfuncs_list_name() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        &sierra_file_name,
+        "--allowed-libfuncs-list-name",
+        "audited",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    let output = String::from_utf8(snapbox.assert().success().get_output().stdout.clone()).unwrap();
+    assert!(serde_json::from_str::<CasmContractClass>(&output).is_ok());
+}
+
+#[test]
+fn disallowed_libfuncs() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let list_file_name = "allowed_libfuncs.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    fs::write(
+        temp_dir.path().join(list_file_name),
+        r#"{"allowed_libfuncs": ["felt252_add"]}"#,
+    )
+    .unwrap();
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--allowed-libfuncs-list-file",
+        list_file_name,
+    ];
+
+    let output = runner(args.clone(), &temp_dir)
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with(
+        "[ERROR] Contract uses libfuncs that are not on the allowed libfuncs list allowed_libfuncs.json:\n"
+    ));
+    assert!(stderr.contains(
+        "  storage_read_syscall in function sierra_1_9_0::HelloStarknet::__wrapper__HelloStarknetImpl__get_balance\n"
+    ));
+    assert!(output.stdout.is_empty());
+
+    let mut args = args;
+    args.push("--allow-disallowed-libfuncs");
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("[WARNING] Libfunc storage_read_syscall in function "));
+    assert!(serde_json::from_slice::<CasmContractClass>(&output.stdout).is_ok());
+}
+
+#[test]
+fn default_allowed_libfuncs_list() {
+    let sierra_file_name = "sierra_1_7_0_trace_hint.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let args = vec!["compile-contract", "--sierra-path", sierra_file_name];
+
+    let output = runner(args.clone(), &temp_dir)
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with(
+        "[ERROR] Contract uses libfuncs that are not on the `audited` allowed libfuncs list:\n"
+    ));
+    assert!(stderr.contains("  trace in function "));
+    assert!(output.stdout.is_empty());
+
+    let mut args = args;
+    args.push("--skip-allowed-libfuncs-check");
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert!(output.stderr.is_empty());
+    assert!(serde_json::from_slice::<CasmContractClass>(&output.stdout).is_ok());
+}
+
+#[test]
+fn unsupported_version() {
+    let sierra_file_name = "sierra_1_10_0.json";
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    copy_sierra_fixture(
+        "sierra_contract",
+        "sierra_1_9_0.json",
+        &temp_dir.path().join(sierra_file_name),
+    );
+    let sierra_path = temp_dir.path().join(sierra_file_name);
+    let mut sierra_json: Value = serde_json::from_slice(&fs::read(&sierra_path).unwrap()).unwrap();
+    sierra_json["sierra_program"][1] = Value::String("0xa".to_string());
+    fs::write(&sierra_path, serde_json::to_vec(&sierra_json).unwrap()).unwrap();
+
+    let args = vec!["compile-contract", "--sierra-path", sierra_file_name];
+    runner(args.clone(), &temp_dir).assert().code(4).stderr_eq(indoc! {r"
+        [ERROR] Unable to compile Sierra to Casm. No matching ContractClass or CasmContractClass found for version 1.10.0. The newest supported version is 1.9, use `--allow-unsupported-version` to compile it with the newest bundled compiler anyway
+    "});
+
+    let mut args = args;
+    args.push("--allow-unsupported-version");
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        indoc! {r"
+            [WARNING] Sierra version 1.10.0 is not supported, the newest supported version is 1.9. Compiling it with the newest bundled compiler, the resulting CASM may be incorrect
+        "}
+    );
+    assert!(serde_json::from_slice::<CasmContractClass>(&output.stdout).is_ok());
+}
+
+#[test]
+fn supported_versions() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let output = runner(vec!["supported-versions"], &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let supported_versions: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(supported_versions["version"], env!("CARGO_PKG_VERSION"));
+    assert!(supported_versions["sierra_versions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|range| range["min"] == serde_json::json!([1, 2])
+            && range["max"] == serde_json::json!([1, 9])));
+}
+
+/// Writes a contract class with Sierra version 1.10.0, which no bundled compiler supports.
+fn write_sierra_1_10_contract(temp_dir: &tempfile::TempDir, sierra_file_name: &str) {
+    let sierra_path = temp_dir.path().join(sierra_file_name);
+    copy_sierra_fixture("sierra_contract", "sierra_1_9_0.json", &sierra_path);
+    let mut sierra_json: Value = serde_json::from_slice(&fs::read(&sierra_path).unwrap()).unwrap();
+    sierra_json["sierra_program"][1] = Value::String("0xa".to_string());
+    fs::write(&sierra_path, serde_json::to_vec(&sierra_json).unwrap()).unwrap();
+}
+
+/// Writes a delegate supporting Sierra 1.10, which runs `compile_script` to compile contracts.
+#[cfg(unix)]
+fn write_delegate(delegate_dir: &std::path::Path, compile_script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let delegate_path = delegate_dir.join("universal-sierra-compiler-next");
+    fs::write(
+        &delegate_path,
+        format!(
+            r#"#!/bin/sh
+if [ "$1" = "supported-versions" ]; then
+    echo '{{"version": "99.0.0", "sierra_versions": [{{"min": [1, 10], "max": [1, 10]}}]}}'
+    exit 0
+fi
+{compile_script}
+"#
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&delegate_path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn delegate_unsupported_version() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sierra_file_name = "sierra_1_10_0.json";
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    write_sierra_1_10_contract(&temp_dir, sierra_file_name);
+
+    // Stands in for a newer USC binary supporting Sierra 1.10.
+    let delegate_dir = temp_dir.path().join("delegates");
+    fs::create_dir(&delegate_dir).unwrap();
+    fs::write(delegate_dir.join("not-usc.txt"), "").unwrap();
+    // Files not named like USC binaries must never be run.
+    let other_path = delegate_dir.join("other-binary");
+    fs::write(&other_path, "#!/bin/sh\ntouch \"$(dirname \"$0\")/ran\"\n").unwrap();
+    fs::set_permissions(&other_path, fs::Permissions::from_mode(0o755)).unwrap();
+    write_delegate(
+        &delegate_dir,
+        &format!(
+            r#"exec "{}" "$@" --allow-unsupported-version"#,
+            snapbox::cmd::cargo_bin!("universal-sierra-compiler").display()
+        ),
+    );
+
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--delegate-dir",
+        "delegates",
+    ];
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    assert!(serde_json::from_slice::<CasmContractClass>(&output.stdout).is_ok());
+    assert!(!delegate_dir.join("ran").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn delegated_contract_libfuncs_are_not_checked() {
+    let sierra_file_name = "sierra_1_10_0.json";
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    write_sierra_1_10_contract(&temp_dir, sierra_file_name);
+    let delegate_dir = temp_dir.path().join("delegates");
+    fs::create_dir(&delegate_dir).unwrap();
+    write_delegate(
+        &delegate_dir,
+        &format!(
+            r#"exec "{}" "$@" --allow-unsupported-version"#,
+            snapbox::cmd::cargo_bin!("universal-sierra-compiler").display()
+        ),
+    );
+
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--delegate-dir",
+        "delegates",
+        "--allowed-libfuncs-list-name",
+        "audited",
+    ];
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    assert!(serde_json::from_slice::<CasmContractClass>(&output.stdout).is_ok());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        indoc! {r"
+            [WARNING] Allowed libfuncs are not checked for contracts compiled by delegates/universal-sierra-compiler-next (USC 99.0.0)
+        "}
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_delegate() {
+    let sierra_file_name = "sierra_1_10_0.json";
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    write_sierra_1_10_contract(&temp_dir, sierra_file_name);
+    let delegate_dir = temp_dir.path().join("delegates");
+    fs::create_dir(&delegate_dir).unwrap();
+    write_delegate(&delegate_dir, "echo 'Out of memory' >&2\nexit 42");
+
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--delegate-dir",
+        "delegates",
+    ];
+    runner(args, &temp_dir)
+        .assert()
+        .code(42)
+        .stderr_eq(indoc! {r"
+            [ERROR] Delegate USC delegates/universal-sierra-compiler-next failed to compile the contract: Out of memory
+        "});
+}
+
+#[cfg(unix)]
+#[test]
+fn delegate_returning_invalid_casm() {
+    let sierra_file_name = "sierra_1_10_0.json";
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    write_sierra_1_10_contract(&temp_dir, sierra_file_name);
+    let delegate_dir = temp_dir.path().join("delegates");
+    fs::create_dir(&delegate_dir).unwrap();
+    write_delegate(&delegate_dir, "echo 'not json'");
+
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--delegate-dir",
+        "delegates",
+    ];
+    runner(args, &temp_dir)
+        .assert()
+        .code(5)
+        .stderr_eq(indoc! {r"
+            [ERROR] Unable to deserialize CASM returned by delegate USC: delegates/universal-sierra-compiler-next
+        "});
+}
+
+/// Entry points calling a function the program doesn't have make the legacy compilers panic.
+#[cfg(any(feature = "sierra-0-1", feature = "sierra-1-0"))]
+#[cfg_attr(feature = "sierra-1-0", test_case("1_0_0", "universal-sierra-compiler-cairo-lang-starknet-proxy 1.0.0-rc0", "1.0.0"; "sierra 1.0.0"))]
+#[cfg_attr(feature = "sierra-0-1", test_case("0_1_0", "cairo-lang-starknet 1.0.0-alpha.6", "0.1.0"; "sierra 0.1.0"))]
+fn legacy_backend_panic(sierra_version: &str, backend: &str, version: &str) {
+    let sierra_file_name = "sierra_".to_string() + sierra_version + ".json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", &sierra_file_name);
+    let sierra_path = temp_dir.path().join(&sierra_file_name);
+    let mut sierra_json: Value = serde_json::from_slice(&fs::read(&sierra_path).unwrap()).unwrap();
+    sierra_json["entry_points_by_type"]["EXTERNAL"][0]["function_idx"] = Value::from(1_000_000);
+    fs::write(&sierra_path, serde_json::to_vec(&sierra_json).unwrap()).unwrap();
+    let args = vec!["compile-contract", "--sierra-path", &sierra_file_name];
+
+    // Only the error is printed, without the report of the default panic hook.
+    runner(args, &temp_dir).assert().code(5).stderr_eq(format!(
+        "[ERROR] {backend} panicked on Sierra version {version}: index out of bounds: [..]\n"
+    ));
+}
+
 #[test]
 fn wrong_json() {
     let sierra_file_name = "wrong_sierra.json";
@@ -227,11 +785,142 @@ fn wrong_json() {
     let temp_dir = temp_dir_with_sierra_file("", sierra_file_name);
     let snapbox = runner(args, &temp_dir);
 
-    snapbox.assert().failure().stderr_eq(indoc! {r"
+    snapbox.assert().code(3).stderr_eq(indoc! {r"
         [ERROR] Unable to read sierra_program. Make sure it is an array of felts
     "});
 }
 
+#[test_case(Value::from(1); "number")]
+#[test_case(Value::from("1"); "short string")]
+fn invalid_sierra_version_felt(felt: Value) {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let sierra_path = temp_dir.path().join(sierra_file_name);
+    let mut sierra_json: Value = serde_json::from_slice(&fs::read(&sierra_path).unwrap()).unwrap();
+    sierra_json["sierra_program"][1] = felt;
+    fs::write(&sierra_path, serde_json::to_vec(&sierra_json).unwrap()).unwrap();
+
+    let args = vec!["compile-contract", "--sierra-path", sierra_file_name];
+    runner(args, &temp_dir)
+        .assert()
+        .code(3)
+        .stderr_eq(indoc! {r"
+        [ERROR] Unable to read sierra_program[1]. Make sure it is a hex encoded felt
+    "});
+}
+
+#[test]
+fn invalid_field() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let sierra_path = temp_dir.path().join(sierra_file_name);
+    let mut sierra_json: Value = serde_json::from_slice(&fs::read(&sierra_path).unwrap()).unwrap();
+    sierra_json["entry_points_by_type"]["EXTERNAL"][0]["selector"] = Value::Bool(true);
+    let sierra_text = serde_json::to_string_pretty(&sierra_json).unwrap();
+    fs::write(&sierra_path, &sierra_text).unwrap();
+    let (line, line_text) = sierra_text
+        .lines()
+        .enumerate()
+        .find(|(_, line_text)| line_text.contains(r#""selector": true"#))
+        .unwrap();
+    let column = line_text.find("true").unwrap() + 1;
+    let args = vec!["compile-contract", "--sierra-path", sierra_file_name];
+
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with(&format!(
+            "[ERROR] Unable to deserialize contract class at `entry_points_by_type.EXTERNAL[0].selector` \
+            (line {}, column {column}): invalid type: boolean `true`",
+            line + 1
+        )),
+        "{stderr}"
+    );
+}
+
+/// Writes a contract class with `add.sierra` as its only external entry point. The function
+/// doesn't withdraw gas, so the fixed cost of entry points can't be enforced on it.
+fn write_contract_without_gas_handling(temp_dir: &tempfile::TempDir, sierra_file_name: &str) {
+    copy_sierra_fixture(
+        "sierra_raw",
+        "add.sierra",
+        &temp_dir.path().join("add.sierra"),
+    );
+    let args = vec![
+        "convert",
+        "--sierra-path",
+        "add.sierra",
+        "--to",
+        "felts",
+        "--output-path",
+        "felts.json",
+    ];
+    runner(args, temp_dir).assert().success();
+
+    let mut sierra_json: Value =
+        serde_json::from_slice(&fs::read(temp_dir.path().join("felts.json")).unwrap()).unwrap();
+    sierra_json["contract_class_version"] = Value::from("0.1.0");
+    sierra_json["entry_points_by_type"] = serde_json::json!({
+        "EXTERNAL": [{ "selector": "0x0", "function_idx": 0 }],
+        "L1_HANDLER": [],
+        "CONSTRUCTOR": [],
+    });
+    sierra_json["abi"] = serde_json::json!([]);
+    fs::write(
+        temp_dir.path().join(sierra_file_name),
+        serde_json::to_vec(&sierra_json).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn failing_statement() {
+    let sierra_file_name = "contract.json";
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    write_contract_without_gas_handling(&temp_dir, sierra_file_name);
+    let args = vec!["compile-contract", "--sierra-path", sierra_file_name];
+
+    runner(args, &temp_dir)
+        .assert()
+        .code(5)
+        .stderr_eq(indoc! {r"
+        [ERROR] [..]
+        Failed at Sierra statement #0 in function `add`, invoking libfunc `felt252_add`:
+          > felt252_add(a, b) -> (c); // 0
+            store_temp_felt252(c) -> (c); // 1
+            return(c); // 2
+    "});
+}
+
+#[test]
+fn failing_statement_in_gas_computation() {
+    let sierra_file_name = "contract.json";
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    write_contract_without_gas_handling(&temp_dir, sierra_file_name);
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--emit",
+        "casm-text",
+    ];
+
+    runner(args, &temp_dir)
+        .assert()
+        .code(5)
+        .stderr_eq(indoc! {r"
+        [ERROR] [..]
+        Failed at Sierra statement #0 in function `add`, invoking libfunc `felt252_add`:
+          > felt252_add(a, b) -> (c); // 0
+            store_temp_felt252(c) -> (c); // 1
+            return(c); // 2
+    "});
+}
+
 #[test_case("1_9_0"; "sierra 1.9.0")]
 #[test_case("1_8_0"; "sierra 1.8.0")]
 #[test_case("1_7_0_trace_hint"; "sierra 1.7.0 with trace hint")]
@@ -241,19 +930,23 @@ fn wrong_json() {
 #[test_case("1_4_0"; "sierra 1.4.0")]
 #[test_case("1_3_0"; "sierra 1.3.0")]
 #[test_case("1_2_0"; "sierra 1.2.0")]
-#[test_case("1_1_0"; "sierra 1.1.0")]
-#[test_case("1_0_0"; "sierra 1.0.0")]
-#[test_case("0_1_0"; "sierra 0.1.0")]
+#[cfg_attr(feature = "sierra-1-0", test_case("1_1_0"; "sierra 1.1.0"))]
+#[cfg_attr(feature = "sierra-1-0", test_case("1_0_0"; "sierra 1.0.0"))]
+#[cfg_attr(feature = "sierra-0-1", test_case("0_1_0"; "sierra 0.1.0"))]
 fn test_happy_case(sierra_version: &str) {
     let sierra_file_name = "sierra_".to_string() + sierra_version + ".json";
     let casm_file_name = "casm.json";
-    let args = vec![
+    let mut args = vec![
         "compile-contract",
         "--sierra-path",
         &sierra_file_name,
         "--output-path",
         casm_file_name,
     ];
+    // `trace` is not on the default `audited` list.
+    if sierra_version == "1_7_0_trace_hint" {
+        args.extend(["--allowed-libfuncs-list-name", "experimental"]);
+    }
 
     let temp_dir = temp_dir_with_sierra_file("sierra_contract", &sierra_file_name);
     let snapbox = runner(args, &temp_dir);
@@ -6,7 +6,12 @@ use indoc::indoc;
 use num_bigint::BigInt;
 use serde_json::Value;
 use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
 use test_case::test_case;
 
 fn verify_output_file(output_path: PathBuf) {
@@ -245,6 +250,80 @@ fn changed_sierra_invalidates_cache() {
     assert!(cached_casm_file(&temp_dir.path().join(cache_dir_name)).is_file());
 }
 
+#[test]
+fn emit_casm_text() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "compile-raw",
+        "--sierra-path",
+        &sierra_file_name,
+        "--emit",
+        "casm-text",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    let output = String::from_utf8(snapbox.assert().success().get_output().stdout.clone()).unwrap();
+    assert!(output.contains("// Function sierra_1_9_0_integrationtest::"));
+    assert!(output.contains("// #0: "));
+    assert!(output.contains("       0: "));
+    assert!(output.contains("ret;"));
+}
+
+#[test]
+fn emit_size_report() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "compile-raw",
+        "--sierra-path",
+        &sierra_file_name,
+        "--emit",
+        "size-report",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    let output = String::from_utf8(snapbox.assert().success().get_output().stdout.clone()).unwrap();
+    assert!(output.starts_with("Bytecode length: "));
+    assert!(output.contains("sierra_1_9_0_integrationtest::"));
+    assert!(output.contains("store_temp"));
+    assert!(output.contains("return"));
+}
+
+#[test]
+fn statement_metadata_is_cached_separately() {
+    let sierra_file_name = "sierra_1_4_0.json";
+    let cache_dir_name = "cache";
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+
+    let run = |output: &str, statement_metadata: bool| -> Value {
+        let mut args = vec![
+            "compile-raw",
+            "--sierra-path",
+            sierra_file_name,
+            "--output-path",
+            output,
+            "--cache-dir",
+            cache_dir_name,
+        ];
+        if statement_metadata {
+            args.push("--statement-metadata");
+        }
+        runner(args, &temp_dir).assert().success();
+        serde_json::from_reader(File::open(temp_dir.path().join(output)).unwrap()).unwrap()
+    };
+
+    let default = run("default.json", false);
+    let with_metadata = run("with_metadata.json", true);
+    let default_again = run("default_again.json", false);
+
+    assert!(default.get("statement_metadata").is_none());
+    assert!(with_metadata["statement_metadata"].is_array());
+    assert_eq!(default, default_again);
+}
+
 #[test]
 fn wrong_json() {
     let sierra_file_name = "wrong_sierra.json";
@@ -260,11 +339,325 @@ fn wrong_json() {
     let temp_dir = temp_dir_with_sierra_file("", sierra_file_name);
     let snapbox = runner(args, &temp_dir);
 
-    snapbox.assert().failure().stderr_eq(indoc! {r"
-        [ERROR] Unable to deserialize Sierra program. Make sure it is in a correct format
+    snapbox.assert().code(3).stderr_eq(indoc! {r"
+        [ERROR] Unable to deserialize Sierra program: missing field `type_declarations`
+        The input is a contract class rather than a Sierra program, compile it with `compile-contract` or `compile`
     "});
 }
 
+#[test]
+fn invalid_field() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let sierra_path = temp_dir.path().join(sierra_file_name);
+    let mut sierra_json: Value = serde_json::from_slice(&fs::read(&sierra_path).unwrap()).unwrap();
+    sierra_json["funcs"][0]["entry_point"] = Value::String("first".to_string());
+    let sierra_text = serde_json::to_string_pretty(&sierra_json).unwrap();
+    fs::write(&sierra_path, &sierra_text).unwrap();
+    let (line, line_text) = sierra_text
+        .lines()
+        .enumerate()
+        .find(|(_, line_text)| line_text.contains(r#""entry_point": "first""#))
+        .unwrap();
+    let column = line_text.find(r#""first""#).unwrap() + 1;
+    let args = vec!["compile-raw", "--sierra-path", sierra_file_name];
+
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with(&format!(
+            "[ERROR] Unable to deserialize Sierra program at `funcs[0].entry_point` \
+            (line {}, column {column}): invalid type: string \"first\"",
+            line + 1
+        )),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn invalid_debug_info_field() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let sierra_path = temp_dir.path().join(sierra_file_name);
+    let mut sierra_json: Value = serde_json::from_slice(&fs::read(&sierra_path).unwrap()).unwrap();
+    sierra_json["debug_info"]["type_names"] = Value::Bool(true);
+    let sierra_text = serde_json::to_string_pretty(&sierra_json).unwrap();
+    fs::write(&sierra_path, &sierra_text).unwrap();
+    let (line, line_text) = sierra_text
+        .lines()
+        .enumerate()
+        .find(|(_, line_text)| line_text.contains(r#""type_names": true"#))
+        .unwrap();
+    let column = line_text.find("true").unwrap() + 1;
+    let args = vec!["compile-raw", "--sierra-path", sierra_file_name];
+
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with(&format!(
+            "[ERROR] Unable to deserialize Sierra program at `debug_info.type_names` \
+            (line {}, column {column}): invalid type: boolean `true`",
+            line + 1
+        )),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn invalid_json_syntax() {
+    let sierra_file_name = "sierra.json";
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join(sierra_file_name),
+        "{\n  \"funcs\": [,]\n}",
+    )
+    .unwrap();
+    let args = vec!["compile-raw", "--sierra-path", sierra_file_name];
+
+    runner(args, &temp_dir)
+        .assert()
+        .code(3)
+        .stderr_eq(indoc! {r"
+        [ERROR] Unable to deserialize Sierra program at `funcs[0]` (line 2, column [..]): expected value
+    "});
+}
+
+#[test]
+fn sierra_text() {
+    let sierra_file_name = "add.sierra";
+    let cairo_program_file_name = "casm.json";
+    let args = vec![
+        "compile-raw",
+        "--sierra-path",
+        &sierra_file_name,
+        "--output-path",
+        cairo_program_file_name,
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    snapbox.assert().success();
+
+    verify_output_file(temp_dir.path().join(cairo_program_file_name));
+}
+
+#[test]
+fn sierra_text_parse_error() {
+    let sierra_file_name = "broken.sierra";
+    let args = vec!["compile-raw", "--sierra-path", &sierra_file_name];
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join(sierra_file_name),
+        "type felt252 = felt252;\nlibfunc ;\n",
+    )
+    .unwrap();
+    let snapbox = runner(args, &temp_dir);
+
+    snapbox.assert().code(3).stderr_eq(indoc! {r"
+        [ERROR] Unable to parse Sierra program at line 2, column 9: Unexpected token `;`, expected one of: [..]
+    "});
+}
+
+#[test]
+fn failing_statement() {
+    let sierra_file_name = "undefined_variable.sierra";
+    let args = vec!["compile-raw", "--sierra-path", &sierra_file_name];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    snapbox.assert().code(5).stderr_eq(indoc! {r"
+        [ERROR] #2: [..] is undefined.
+        Failed at Sierra statement #2 in function `add`, returning:
+            felt252_add(a, b) -> (c); // 0
+            store_temp_felt252(c) -> (c); // 1
+          > return(a); // 2
+    "});
+}
+
+/// Watching process, killed when the test ends, even if it fails.
+struct WatchProcess(Child);
+
+impl Drop for WatchProcess {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn watch() {
+    let sierra_file_name = "add.sierra";
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let mut process = WatchProcess(
+        std::process::Command::new(snapbox::cmd::cargo_bin!("universal-sierra-compiler"))
+            .current_dir(temp_dir.path())
+            .args([
+                "compile-raw",
+                "--sierra-path",
+                sierra_file_name,
+                "--output-path",
+                "casm.json",
+                "--watch",
+            ])
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap(),
+    );
+    let stderr = BufReader::new(process.0.stderr.take().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in stderr.lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let next_line = || receiver.recv_timeout(Duration::from_secs(60)).unwrap();
+    let bytecode_length = || {
+        let output = fs::read(temp_dir.path().join("casm.json")).unwrap();
+        let output: Value = serde_json::from_slice(&output).unwrap();
+        output["assembled_cairo_program"]["bytecode"]
+            .as_array()
+            .unwrap()
+            .len()
+    };
+
+    assert!(next_line().starts_with("[WATCH] Compiled add.sierra in "));
+    let initial_bytecode_length = bytecode_length();
+
+    // The input is replaced at once, so that the watcher never reads it half written.
+    let changed_path = temp_dir.path().join("add_twice.sierra");
+    copy_sierra_fixture("sierra_raw", "add_twice.sierra", &changed_path);
+    File::options()
+        .write(true)
+        .open(&changed_path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(2))
+        .unwrap();
+    fs::rename(&changed_path, temp_dir.path().join(sierra_file_name)).unwrap();
+
+    assert!(next_line().starts_with("[WATCH] Compiled add.sierra in "));
+    assert!(bytecode_length() > initial_bytecode_length);
+    assert!(receiver.recv_timeout(Duration::from_secs(2)).is_err());
+}
+
+#[test]
+fn missing_input_file() {
+    let args = vec!["compile-raw", "--sierra-path", "missing.json"];
+
+    let temp_dir = TempDir::new().unwrap();
+    let snapbox = runner(args, &temp_dir);
+
+    snapbox.assert().code(6).stderr_eq(indoc! {r"
+        [ERROR] Unable to read Sierra input file: missing.json
+    "});
+}
+
+#[test]
+fn sierra_version() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let cairo_program_file_name = "casm.json";
+    let args = vec![
+        "compile-raw",
+        "--sierra-path",
+        &sierra_file_name,
+        "--output-path",
+        cairo_program_file_name,
+        "--sierra-version",
+        "1.9.0",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    snapbox.assert().success();
+
+    verify_output_file(temp_dir.path().join(cairo_program_file_name));
+}
+
+#[cfg(any(feature = "sierra-0-1", feature = "sierra-1-0"))]
+#[cfg_attr(feature = "sierra-1-0", test_case("1.1.0"; "sierra 1.1.0"))]
+#[cfg_attr(feature = "sierra-1-0", test_case("1.0.0"; "sierra 1.0.0"))]
+#[cfg_attr(feature = "sierra-0-1", test_case("0.1.0"; "sierra 0.1.0"))]
+fn legacy_sierra_version(sierra_version: &str) {
+    let sierra_file_name = "append.sierra";
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let compile = |sierra_version: &str, output_file_name: &str| -> Value {
+        let args = vec![
+            "compile-raw",
+            "--sierra-path",
+            sierra_file_name,
+            "--output-path",
+            output_file_name,
+            "--sierra-version",
+            sierra_version,
+        ];
+        runner(args, &temp_dir).assert().success();
+        verify_output_file(temp_dir.path().join(output_file_name));
+
+        let file = File::open(temp_dir.path().join(output_file_name)).unwrap();
+        serde_json::from_reader(file).unwrap()
+    };
+    let legacy_output = compile(sierra_version, "legacy.json");
+    let latest_output = compile("1.9.0", "latest.json");
+
+    let hint_offsets = |output: &Value| -> Vec<Value> {
+        output["assembled_cairo_program"]["hints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hint| hint[0].clone())
+            .collect()
+    };
+    assert!(!hint_offsets(&legacy_output).is_empty());
+    assert_eq!(hint_offsets(&legacy_output), hint_offsets(&latest_output));
+
+    let cost_token_names = |output: &Value| -> Vec<String> {
+        output["function_costs"]["0"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
+    };
+    assert_eq!(
+        cost_token_names(&legacy_output),
+        cost_token_names(&latest_output)
+    );
+}
+
+#[test_case("1.9", "Invalid Sierra version 1.9, expected the MAJOR.MINOR.PATCH format", 2; "invalid")]
+#[test_case("9.0.0", "Unable to compile Sierra to Casm. No compiler supports Sierra version 9.0.0", 4; "unsupported")]
+fn wrong_sierra_version(sierra_version: &str, error: &str, exit_code: i32) {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "compile-raw",
+        "--sierra-path",
+        &sierra_file_name,
+        "--sierra-version",
+        sierra_version,
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let snapbox = runner(args, &temp_dir);
+
+    snapbox
+        .assert()
+        .code(exit_code)
+        .stderr_eq(format!("[ERROR] {error}\n"));
+}
+
 #[test_case("1_9_0"; "sierra 1.9.0")]
 #[test_case("1_8_0"; "sierra 1.8.0")]
 #[test_case("1_7_0_trace_hint"; "sierra 1.7.0 with trace hint")]
@@ -0,0 +1,139 @@
+use crate::e2e::{cache_files, copy_sierra_fixture, runner};
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use indoc::indoc;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn read_output(path: &Path) -> Value {
+    let file = File::open(path).unwrap();
+    serde_json::from_reader(file).unwrap()
+}
+
+fn scarb_target_dir(with_index: bool) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    copy_sierra_fixture(
+        "sierra_contract",
+        "sierra_1_9_0.json",
+        &temp_dir
+            .path()
+            .join("pkg_HelloStarknet.contract_class.json"),
+    );
+    copy_sierra_fixture(
+        "sierra_raw",
+        "sierra_1_9_0.json",
+        &temp_dir.path().join("pkg.sierra.json"),
+    );
+    if with_index {
+        fs::write(
+            temp_dir.path().join("pkg.starknet_artifacts.json"),
+            r#"{"version": 1, "contracts": [{"id": "1", "package_name": "pkg", "contract_name": "HelloStarknet", "module_path": "pkg::HelloStarknet", "artifacts": {"sierra": "pkg_HelloStarknet.contract_class.json", "casm": null}}]}"#,
+        )
+        .unwrap();
+    }
+    temp_dir
+}
+
+fn verify_outputs(temp_dir: &TempDir) {
+    let contract_output = read_output(
+        &temp_dir
+            .path()
+            .join("pkg_HelloStarknet.compiled_contract_class.json"),
+    );
+    assert!(serde_json::from_value::<CasmContractClass>(contract_output).is_ok());
+
+    let program_output = read_output(&temp_dir.path().join("pkg.compiled_contract_class.json"));
+    assert!(program_output.get("assembled_cairo_program").is_some());
+}
+
+#[test]
+fn artifacts_index() {
+    let temp_dir = scarb_target_dir(true);
+    // Contracts missing from the index are left out.
+    copy_sierra_fixture(
+        "sierra_contract",
+        "sierra_1_8_0.json",
+        &temp_dir.path().join("pkg_Other.contract_class.json"),
+    );
+    let args = vec!["compile-target", "--target-dir", "."];
+
+    runner(args, &temp_dir)
+        .assert()
+        .success()
+        .stdout_eq("Compiled 2 of 2 artifacts in .\n");
+
+    verify_outputs(&temp_dir);
+    assert!(!temp_dir
+        .path()
+        .join("pkg_Other.compiled_contract_class.json")
+        .exists());
+}
+
+#[test]
+fn scanned_directory() {
+    let temp_dir = scarb_target_dir(false);
+    let args = vec!["compile-target", "--target-dir", ".", "--jobs", "1"];
+
+    runner(args, &temp_dir)
+        .assert()
+        .success()
+        .stdout_eq("Compiled 2 of 2 artifacts in .\n");
+
+    verify_outputs(&temp_dir);
+}
+
+#[test]
+fn cached_artifacts() {
+    let temp_dir = scarb_target_dir(true);
+    let args = vec![
+        "compile-target",
+        "--target-dir",
+        ".",
+        "--cache-dir",
+        "cache",
+    ];
+
+    runner(args.clone(), &temp_dir).assert().success();
+    let cached = cache_files(&temp_dir.path().join("cache"));
+    assert_eq!(cached.len(), 4);
+
+    runner(args, &temp_dir).assert().success();
+    assert_eq!(cache_files(&temp_dir.path().join("cache")), cached);
+    verify_outputs(&temp_dir);
+}
+
+#[test]
+fn failed_artifact() {
+    let temp_dir = scarb_target_dir(false);
+    copy_sierra_fixture(
+        "",
+        "wrong_sierra.json",
+        &temp_dir.path().join("broken.contract_class.json"),
+    );
+    let args = vec!["compile-target", "--target-dir", "."];
+
+    runner(args, &temp_dir)
+        .assert()
+        .code(1)
+        .stdout_eq("Compiled 2 of 3 artifacts in ., 1 failed\n")
+        .stderr_eq(indoc! {r"
+            [ERROR] [..]
+            Unable to compile ./broken.contract_class.json
+        "});
+
+    verify_outputs(&temp_dir);
+}
+
+#[test]
+fn empty_target_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let args = vec!["compile-target", "--target-dir", "."];
+
+    runner(args, &temp_dir)
+        .assert()
+        .code(3)
+        .stderr_eq(indoc! {r"
+        [ERROR] No contracts or Sierra programs found in target directory: .
+    "});
+}
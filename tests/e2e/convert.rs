@@ -0,0 +1,189 @@
+use crate::e2e::{runner, temp_dir_with_sierra_file};
+use indoc::indoc;
+use serde_json::Value;
+use std::fs::{self, File};
+use test_case::test_case;
+
+fn read_json(temp_dir: &tempfile::TempDir, file_name: &str) -> Value {
+    let file = File::open(temp_dir.path().join(file_name)).unwrap();
+    serde_json::from_reader(file).unwrap()
+}
+
+#[test_case("1_9_0"; "sierra 1.9.0")]
+#[cfg_attr(feature = "sierra-1-0", test_case("1_1_0"; "sierra 1.1.0"))]
+#[cfg_attr(feature = "sierra-1-0", test_case("1_0_0"; "sierra 1.0.0"))]
+#[cfg_attr(feature = "sierra-0-1", test_case("0_1_0"; "sierra 0.1.0"))]
+fn contract_to_sierra_text(sierra_version: &str) {
+    let sierra_file_name = "sierra_".to_string() + sierra_version + ".json";
+    let args = vec![
+        "convert",
+        "--sierra-path",
+        &sierra_file_name,
+        "--to",
+        "sierra-text",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", &sierra_file_name);
+    let output = runner(args, &temp_dir).assert().success();
+
+    let sierra_text = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(sierra_text.contains("libfunc "));
+    assert!(sierra_text.contains("return("));
+}
+
+#[test]
+fn contract_round_trip() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+
+    let args = vec![
+        "convert",
+        "--sierra-path",
+        sierra_file_name,
+        "--to",
+        "program-json",
+        "--output-path",
+        "program.json",
+    ];
+    runner(args, &temp_dir).assert().success();
+
+    let args = vec![
+        "convert",
+        "--sierra-path",
+        "program.json",
+        "--to",
+        "felts",
+        "--output-path",
+        "felts.json",
+    ];
+    runner(args, &temp_dir).assert().success();
+
+    let mut sierra_json = read_json(&temp_dir, sierra_file_name);
+    let felts = read_json(&temp_dir, "felts.json");
+    sierra_json["sierra_program"] = felts["sierra_program"].clone();
+    sierra_json["sierra_program_debug_info"] = felts["sierra_program_debug_info"].clone();
+    fs::write(
+        temp_dir.path().join("encoded.json"),
+        serde_json::to_vec(&sierra_json).unwrap(),
+    )
+    .unwrap();
+
+    let args = vec![
+        "convert",
+        "--sierra-path",
+        "encoded.json",
+        "--to",
+        "program-json",
+        "--output-path",
+        "decoded.json",
+    ];
+    runner(args, &temp_dir).assert().success();
+
+    assert_eq!(
+        read_json(&temp_dir, "program.json"),
+        read_json(&temp_dir, "decoded.json")
+    );
+}
+
+#[test]
+fn sierra_text_to_felts() {
+    let sierra_file_name = "add.sierra";
+    let args = vec![
+        "convert",
+        "--sierra-path",
+        sierra_file_name,
+        "--to",
+        "felts",
+        "--output-path",
+        "felts.json",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    runner(args, &temp_dir).assert().success();
+
+    let felts = read_json(&temp_dir, "felts.json");
+    assert!(!felts["sierra_program"].as_array().unwrap().is_empty());
+}
+
+/// Reads the Sierra version, e.g. `1.9.0`, from the first felts of the encoded program.
+fn encoded_sierra_version(felts: &Value) -> String {
+    felts["sierra_program"].as_array().unwrap()[..3]
+        .iter()
+        .map(|felt| {
+            let felt = felt.as_str().unwrap().trim_start_matches("0x");
+            u64::from_str_radix(felt, 16).unwrap().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[test_case(None, "1.9.0"; "newest")]
+#[test_case(Some("1.9.0"), "1.9.0"; "sierra 1.9.0")]
+#[test_case(Some("1.2.0"), "1.2.0"; "sierra 1.2.0")]
+#[cfg_attr(feature = "sierra-1-0", test_case(Some("1.1.0"), "1.1.0"; "sierra 1.1.0"))]
+#[cfg_attr(feature = "sierra-1-0", test_case(Some("1.0.0"), "1.0.0"; "sierra 1.0.0"))]
+fn sierra_version_of_felts(sierra_version: Option<&str>, encoded_version: &str) {
+    let sierra_file_name = "add.sierra";
+    let mut args = vec![
+        "convert",
+        "--sierra-path",
+        sierra_file_name,
+        "--to",
+        "felts",
+        "--output-path",
+        "felts.json",
+    ];
+    if let Some(sierra_version) = sierra_version {
+        args.extend(["--sierra-version", sierra_version]);
+    }
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    runner(args, &temp_dir).assert().success();
+
+    let felts = read_json(&temp_dir, "felts.json");
+    assert_eq!(encoded_sierra_version(&felts), encoded_version);
+}
+
+#[cfg(feature = "sierra-0-1")]
+#[test]
+fn sierra_0_1_to_felts() {
+    let sierra_file_name = "add.sierra";
+    let args = vec![
+        "convert",
+        "--sierra-path",
+        sierra_file_name,
+        "--to",
+        "felts",
+        "--sierra-version",
+        "0.1.0",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    runner(args, &temp_dir)
+        .assert()
+        .code(4)
+        .stderr_eq(indoc! {r"
+            [ERROR] Encoding Sierra programs as felts is only supported for Sierra 1.0.0 and newer
+        "});
+}
+
+#[test]
+fn contract_to_felts() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "convert",
+        "--sierra-path",
+        sierra_file_name,
+        "--to",
+        "felts",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+
+    runner(args, &temp_dir)
+        .assert()
+        .code(2)
+        .stderr_eq(indoc! {r"
+            [ERROR] The Sierra program of the contract class is already encoded as felts
+        "});
+}
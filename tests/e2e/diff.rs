@@ -0,0 +1,326 @@
+use crate::e2e::{copy_sierra_fixture, runner, temp_dir_with_sierra_file};
+use serde_json::Value;
+use std::fs::{self, File};
+use tempfile::TempDir;
+use test_case::test_case;
+
+fn compile_raw(sierra_file_name: &str, output_file_name: &str, temp_dir: &TempDir) {
+    let args = vec![
+        "compile-raw",
+        "--sierra-path",
+        sierra_file_name,
+        "--output-path",
+        output_file_name,
+    ];
+    runner(args, temp_dir).assert().success();
+}
+
+#[test]
+fn identical_contracts() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "diff",
+        "--old-path",
+        sierra_file_name,
+        "--new-path",
+        sierra_file_name,
+        "--output-path",
+        "diff.json",
+        "--max-bytecode-length-increase",
+        "0",
+        "--max-gas-increase",
+        "0",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    runner(args, &temp_dir).assert().success();
+
+    let diff: Value =
+        serde_json::from_reader(File::open(temp_dir.path().join("diff.json")).unwrap()).unwrap();
+    assert_eq!(diff["bytecode_length"]["delta"], 0);
+    assert_eq!(diff["compiled_class_hash"]["changed"], false);
+    assert!(diff["hints"]["added"].as_array().unwrap().is_empty());
+    assert!(diff["hints"]["removed"].as_array().unwrap().is_empty());
+
+    let entry_points = diff["entry_points"].as_array().unwrap();
+    assert!(!entry_points.is_empty());
+    for entry_point in entry_points {
+        assert_eq!(entry_point["bytecode_length_delta"], 0);
+        assert!(entry_point["gas_deltas"]
+            .as_object()
+            .unwrap()
+            .values()
+            .all(|delta| delta == 0));
+    }
+}
+
+#[test]
+fn compiled_and_sierra_contract() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let casm_file_name = "casm.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+
+    runner(
+        vec![
+            "compile-contract",
+            "--sierra-path",
+            sierra_file_name,
+            "--output-path",
+            casm_file_name,
+        ],
+        &temp_dir,
+    )
+    .assert()
+    .success();
+
+    let args = vec![
+        "diff",
+        "--old-path",
+        casm_file_name,
+        "--new-path",
+        sierra_file_name,
+    ];
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let diff: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(diff["compiled_class_hash"]["changed"], false);
+    assert_eq!(diff["bytecode_length"]["delta"], 0);
+}
+
+#[test]
+fn entry_point_lengths_of_sierra_contract() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let entry_point_costs_file_name = "entry_point_costs.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+
+    runner(
+        vec![
+            "compile-contract",
+            "--sierra-path",
+            sierra_file_name,
+            "--entry-point-costs-path",
+            entry_point_costs_file_name,
+        ],
+        &temp_dir,
+    )
+    .assert()
+    .success();
+    let entry_point_costs: Value = serde_json::from_reader(
+        File::open(temp_dir.path().join(entry_point_costs_file_name)).unwrap(),
+    )
+    .unwrap();
+
+    let args = vec![
+        "diff",
+        "--old-path",
+        sierra_file_name,
+        "--new-path",
+        sierra_file_name,
+    ];
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let diff: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let external = entry_point_costs["entry_points_by_type"]["EXTERNAL"]
+        .as_array()
+        .unwrap();
+    assert!(!external.is_empty());
+    for entry_point_cost in external {
+        let name = format!(
+            "EXTERNAL {}",
+            entry_point_cost["selector"].as_str().unwrap()
+        );
+        let entry_point = diff["entry_points"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry_point| entry_point["name"] == name.as_str())
+            .unwrap();
+        assert_eq!(entry_point["old"]["offset"], entry_point_cost["offset"]);
+        assert_eq!(
+            entry_point["old"]["bytecode_length"],
+            entry_point_cost["bytecode_length"]
+        );
+        assert_eq!(entry_point["old"]["costs"], entry_point_cost["costs"]);
+    }
+}
+
+#[test]
+fn textual_and_compiled_program() {
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", "add.sierra");
+    compile_raw("add.sierra", "add.json", &temp_dir);
+
+    let args = vec!["diff", "--old-path", "add.sierra", "--new-path", "add.json"];
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let diff: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(diff["bytecode_length"]["delta"], 0);
+    let entry_points = diff["entry_points"].as_array().unwrap();
+    assert_eq!(entry_points.len(), 1);
+    assert_eq!(entry_points[0]["old"], entry_points[0]["new"]);
+}
+
+#[test]
+fn exceeded_threshold() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "diff",
+        "--old-path",
+        sierra_file_name,
+        "--new-path",
+        sierra_file_name,
+        "--max-bytecode-length-increase=-1",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(1)
+        .get_output()
+        .clone();
+    let diff: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert!(!diff["exceeded_thresholds"].as_array().unwrap().is_empty());
+    assert!(diff["compiled_class_hash"].is_null());
+    assert!(diff["entry_points"][0]["name"]
+        .as_str()
+        .unwrap()
+        .starts_with("sierra_1_9_0"));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("increased by 0, more than -1"));
+}
+
+#[test]
+fn unrecognized_input() {
+    let sierra_file_name = "wrong_sierra.json";
+    let args = vec![
+        "diff",
+        "--old-path",
+        sierra_file_name,
+        "--new-path",
+        sierra_file_name,
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("", sierra_file_name);
+    runner(args, &temp_dir).assert().code(3);
+}
+
+#[test_case("debug_info"; "debug info")]
+#[test_case("function_costs"; "function costs")]
+fn malformed_compile_raw_output(field: &str) {
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", "add.sierra");
+    compile_raw("add.sierra", "compiled.json", &temp_dir);
+    let compiled_path = temp_dir.path().join("compiled.json");
+    let mut compiled: Value = serde_json::from_slice(&fs::read(&compiled_path).unwrap()).unwrap();
+    compiled[field] = Value::Null;
+    fs::write(&compiled_path, serde_json::to_vec(&compiled).unwrap()).unwrap();
+    let args = vec![
+        "diff",
+        "--old-path",
+        "compiled.json",
+        "--new-path",
+        "compiled.json",
+    ];
+
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "[ERROR] Unable to process the old input\n"
+    );
+}
+
+#[test]
+fn different_programs() {
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", "add.sierra");
+    copy_sierra_fixture(
+        "sierra_raw",
+        "add_twice.sierra",
+        &temp_dir.path().join("add_twice.sierra"),
+    );
+    compile_raw("add.sierra", "old.json", &temp_dir);
+    compile_raw("add_twice.sierra", "new.json", &temp_dir);
+    let args = vec![
+        "diff",
+        "--old-path",
+        "old.json",
+        "--new-path",
+        "new.json",
+        "--max-gas-increase",
+        "0",
+    ];
+
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(1)
+        .get_output()
+        .clone();
+    let diff: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(diff["bytecode_length"]["old"], 2);
+    assert_eq!(diff["bytecode_length"]["new"], 3);
+    assert_eq!(diff["bytecode_length"]["delta"], 1);
+
+    let entry_points = diff["entry_points"].as_array().unwrap();
+    assert_eq!(entry_points.len(), 1);
+    assert_eq!(entry_points[0]["bytecode_length_delta"], 1);
+    let const_delta = entry_points[0]["gas_deltas"]["const"].as_i64().unwrap();
+    assert!(const_delta > 0);
+
+    let exceeded_thresholds = diff["exceeded_thresholds"].as_array().unwrap();
+    assert_eq!(exceeded_thresholds.len(), 1);
+    assert!(exceeded_thresholds[0]
+        .as_str()
+        .unwrap()
+        .starts_with("const gas cost of"));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains(&format!("increased by {const_delta}, more than 0")));
+}
+
+#[test]
+fn changed_hints() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_raw", sierra_file_name);
+    copy_sierra_fixture(
+        "sierra_raw",
+        "add.sierra",
+        &temp_dir.path().join("add.sierra"),
+    );
+    compile_raw("add.sierra", "add.json", &temp_dir);
+
+    let diff_hints = |old_path: &str, new_path: &str| -> Value {
+        let args = vec!["diff", "--old-path", old_path, "--new-path", new_path];
+        let output = runner(args, &temp_dir)
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        let diff: Value = serde_json::from_slice(&output.stdout).unwrap();
+        diff["hints"].clone()
+    };
+
+    let hints = diff_hints("add.json", sierra_file_name);
+    assert!(hints["removed"].as_array().unwrap().is_empty());
+    let added_hints = hints["added"].as_array().unwrap().clone();
+    assert!(!added_hints.is_empty());
+
+    let hints = diff_hints(sierra_file_name, "add.json");
+    assert_eq!(hints["removed"].as_array().unwrap(), &added_hints);
+    assert!(hints["added"].as_array().unwrap().is_empty());
+}
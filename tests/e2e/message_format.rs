@@ -0,0 +1,192 @@
+use crate::e2e::{runner, temp_dir_with_sierra_file};
+use serde_json::{json, Value};
+use std::fs;
+
+fn json_messages(stderr: &[u8]) -> Vec<Value> {
+    String::from_utf8(stderr.to_vec())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn kinds(messages: &[Value]) -> Vec<&str> {
+    messages
+        .iter()
+        .map(|message| message["kind"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn error_with_causes() {
+    let sierra_file_name = "wrong_sierra.json";
+    let args = vec![
+        "compile-raw",
+        "--sierra-path",
+        sierra_file_name,
+        "--message-format",
+        "json",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("", sierra_file_name);
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+
+    let messages = json_messages(&output.stderr);
+    assert_eq!(messages.len(), 1);
+    let error = &messages[0];
+    assert_eq!(error["kind"], "error");
+    assert_eq!(error["input_path"], sierra_file_name);
+    assert_eq!(
+        error["message"],
+        "Unable to deserialize Sierra program: missing field `type_declarations`\n\
+        The input is a contract class rather than a Sierra program, compile it with `compile-contract` or `compile`"
+    );
+    assert_eq!(error["causes"], json!([]));
+}
+
+#[cfg(feature = "sierra-1-0")]
+#[test]
+fn backend_panic() {
+    let sierra_file_name = "sierra_1_0_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let sierra_path = temp_dir.path().join(sierra_file_name);
+    let mut sierra_json: Value = serde_json::from_slice(&fs::read(&sierra_path).unwrap()).unwrap();
+    sierra_json["entry_points_by_type"]["EXTERNAL"][0]["function_idx"] = Value::from(1_000_000);
+    fs::write(&sierra_path, serde_json::to_vec(&sierra_json).unwrap()).unwrap();
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--message-format",
+        "json",
+    ];
+
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(5)
+        .get_output()
+        .clone();
+
+    // The panic is only reported as the error, which keeps every line of stderr JSON.
+    let messages = json_messages(&output.stderr);
+    assert_eq!(kinds(&messages), ["error"]);
+    assert!(messages[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("panicked on Sierra version 1.0.0"));
+}
+
+#[test]
+fn usage_error() {
+    let args = vec!["compile-raw", "--message-format", "json", "--unknown-flag"];
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let output = runner(args, &temp_dir)
+        .assert()
+        .code(2)
+        .get_output()
+        .clone();
+
+    let messages = json_messages(&output.stderr);
+    assert_eq!(kinds(&messages), ["error"]);
+    assert!(messages[0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("unexpected argument '--unknown-flag' found"));
+}
+
+#[test]
+fn cache_events_and_timing() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--output-path",
+        "casm.json",
+        "--cache-dir",
+        "cache",
+        "--message-format",
+        "json",
+    ];
+
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    let output = runner(args.clone(), &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let messages = json_messages(&output.stderr);
+    assert_eq!(kinds(&messages), ["cache-miss", "timing"]);
+    assert_eq!(messages[0]["input_path"], sierra_file_name);
+    assert_eq!(messages[0]["causes"], json!([]));
+    assert!(messages[1]["elapsed_ms"].is_u64());
+
+    let output = runner(args.clone(), &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert_eq!(
+        kinds(&json_messages(&output.stderr)),
+        ["cache-hit", "timing"]
+    );
+
+    // Supported versions never fall back to the newest compiler, so the flag doesn't matter.
+    let mut args = args;
+    args.push("--allow-unsupported-version");
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert_eq!(
+        kinds(&json_messages(&output.stderr)),
+        ["cache-hit", "timing"]
+    );
+}
+
+#[test]
+fn warnings() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let list_file_name = "allowed_libfuncs.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    fs::write(
+        temp_dir.path().join(list_file_name),
+        r#"{"allowed_libfuncs": ["felt252_add"]}"#,
+    )
+    .unwrap();
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--output-path",
+        "casm.json",
+        "--allowed-libfuncs-list-file",
+        list_file_name,
+        "--allow-disallowed-libfuncs",
+        "--message-format",
+        "json",
+    ];
+
+    let output = runner(args, &temp_dir)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let messages = json_messages(&output.stderr);
+    let warning = messages
+        .iter()
+        .find(|message| message["kind"] == "warning")
+        .unwrap();
+    assert_eq!(warning["input_path"], sierra_file_name);
+    assert!(warning["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Libfunc storage_read_syscall in function "));
+}
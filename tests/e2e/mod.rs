@@ -3,8 +3,14 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
+mod compile;
 mod compile_contract;
 mod compile_raw;
+mod compile_target;
+mod convert;
+mod diff;
+mod message_format;
+mod verify;
 
 #[must_use]
 fn runner(args: Vec<&str>, temp_dir: &TempDir) -> Command {
@@ -0,0 +1,90 @@
+use crate::e2e::{runner, temp_dir_with_sierra_file};
+use indoc::indoc;
+use serde_json::Value;
+use std::fs::{self, File};
+use test_case::test_case;
+
+fn compile_contract_to_file(temp_dir: &tempfile::TempDir, sierra_file_name: &str) {
+    let args = vec![
+        "compile-contract",
+        "--sierra-path",
+        sierra_file_name,
+        "--output-path",
+        "casm.json",
+    ];
+    runner(args, temp_dir).assert().success();
+}
+
+#[test_case("1_9_0"; "sierra 1.9.0")]
+#[cfg_attr(feature = "sierra-1-0", test_case("1_0_0"; "sierra 1.0.0"))]
+#[cfg_attr(feature = "sierra-0-1", test_case("0_1_0"; "sierra 0.1.0"))]
+fn matching_casm(sierra_version: &str) {
+    let sierra_file_name = "sierra_".to_string() + sierra_version + ".json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", &sierra_file_name);
+    compile_contract_to_file(&temp_dir, &sierra_file_name);
+
+    let args = vec![
+        "verify",
+        "--sierra-path",
+        &sierra_file_name,
+        "--casm-path",
+        "casm.json",
+    ];
+    runner(args, &temp_dir)
+        .assert()
+        .success()
+        .stdout_eq("CASM matches the compiled Sierra\n");
+}
+
+#[test]
+fn mismatched_bytecode() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    compile_contract_to_file(&temp_dir, sierra_file_name);
+
+    let casm_path = temp_dir.path().join("casm.json");
+    let mut casm_json: Value = serde_json::from_reader(File::open(&casm_path).unwrap()).unwrap();
+    let original = casm_json["bytecode"][1].clone();
+    casm_json["bytecode"][1] = Value::String("0x1234".to_string());
+    fs::write(&casm_path, serde_json::to_vec(&casm_json).unwrap()).unwrap();
+
+    let args = vec![
+        "verify",
+        "--sierra-path",
+        sierra_file_name,
+        "--casm-path",
+        "casm.json",
+    ];
+    runner(args, &temp_dir)
+        .assert()
+        .code(1)
+        .stderr_eq(format!(
+            "[ERROR] CASM does not match the compiled Sierra. Bytecode differs at index 1: expected {original}, found \"0x1234\"\n"
+        ));
+}
+
+#[test]
+fn mismatched_entry_points() {
+    let sierra_file_name = "sierra_1_9_0.json";
+    let temp_dir = temp_dir_with_sierra_file("sierra_contract", sierra_file_name);
+    compile_contract_to_file(&temp_dir, sierra_file_name);
+
+    let casm_path = temp_dir.path().join("casm.json");
+    let mut casm_json: Value = serde_json::from_reader(File::open(&casm_path).unwrap()).unwrap();
+    casm_json["entry_points_by_type"]["EXTERNAL"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+    fs::write(&casm_path, serde_json::to_vec(&casm_json).unwrap()).unwrap();
+
+    let args = vec![
+        "verify",
+        "--sierra-path",
+        sierra_file_name,
+        "--casm-path",
+        "casm.json",
+    ];
+    runner(args, &temp_dir).assert().code(1).stderr_eq(indoc! {r"
+        [ERROR] CASM does not match the compiled Sierra. Number of EXTERNAL entry points differs: expected 2, found 1
+    "});
+}
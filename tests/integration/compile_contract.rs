@@ -1,6 +1,11 @@
 use std::fs::File;
+use std::ops::RangeInclusive;
 use test_case::test_case;
-use universal_sierra_compiler::compile_contract;
+use universal_sierra_compiler::{
+    compile_contract, compile_contract_with_options, compile_contract_with_registry,
+    find_disallowed_libfuncs, AllowedLibfuncsList, BackendPanic, BackendRegistry,
+    CompileContractOptions, SierraBackend,
+};
 
 #[test]
 fn wrong_json() {
@@ -21,9 +26,9 @@ fn wrong_json() {
 #[test_case("1_4_0"; "sierra 1.4.0")]
 #[test_case("1_3_0"; "sierra 1.3.0")]
 #[test_case("1_2_0"; "sierra 1.2.0")]
-#[test_case("1_1_0"; "sierra 1.1.0")]
-#[test_case("1_0_0"; "sierra 1.0.0")]
-#[test_case("0_1_0"; "sierra 0.1.0")]
+#[cfg_attr(feature = "sierra-1-0", test_case("1_1_0"; "sierra 1.1.0"))]
+#[cfg_attr(feature = "sierra-1-0", test_case("1_0_0"; "sierra 1.0.0"))]
+#[cfg_attr(feature = "sierra-0-1", test_case("0_1_0"; "sierra 0.1.0"))]
 fn compile_sierra(sierra_version: &str) {
     let file =
         File::open("tests/data/sierra_contract/sierra_".to_string() + sierra_version + ".json")
@@ -33,3 +38,178 @@ fn compile_sierra(sierra_version: &str) {
     let casm_class = compile_contract(sierra_json);
     assert!(casm_class.is_ok());
 }
+
+#[test_case("1_9_0"; "sierra 1.9.0")]
+#[test_case("1_2_0"; "sierra 1.2.0")]
+fn allowed_libfuncs(sierra_version: &str) {
+    let file =
+        File::open("tests/data/sierra_contract/sierra_".to_string() + sierra_version + ".json")
+            .unwrap();
+    let sierra_json: serde_json::Value = serde_json::from_reader(file).unwrap();
+
+    let options = CompileContractOptions {
+        allowed_libfuncs_list: Some(AllowedLibfuncsList::Name("all".to_string())),
+        ..CompileContractOptions::default()
+    };
+    assert!(compile_contract_with_options(sierra_json, &options).is_ok());
+}
+
+#[test]
+fn disallowed_libfuncs() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let list_path = temp_dir.path().join("allowed_libfuncs.json");
+    std::fs::write(
+        &list_path,
+        r#"{"allowed_libfuncs": ["felt252_add", "store_temp"]}"#,
+    )
+    .unwrap();
+    let list = AllowedLibfuncsList::File(list_path);
+    let file = File::open("tests/data/sierra_contract/sierra_1_9_0.json").unwrap();
+    let sierra_json: serde_json::Value = serde_json::from_reader(file).unwrap();
+
+    let disallowed_libfuncs = find_disallowed_libfuncs(&sierra_json, &list).unwrap();
+    assert!(disallowed_libfuncs.iter().any(|disallowed_libfunc| {
+        disallowed_libfunc.libfunc == "storage_read_syscall"
+            && disallowed_libfunc.function
+                == "sierra_1_9_0::HelloStarknet::__wrapper__HelloStarknetImpl__get_balance"
+    }));
+    assert!(disallowed_libfuncs
+        .iter()
+        .all(|disallowed_libfunc| !["felt252_add", "store_temp"]
+            .contains(&disallowed_libfunc.libfunc.as_str())));
+
+    let options = CompileContractOptions {
+        allowed_libfuncs_list: Some(list),
+        ..CompileContractOptions::default()
+    };
+    let error = compile_contract_with_options(sierra_json, &options).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("  storage_read_syscall in function sierra_1_9_0::HelloStarknet::"));
+}
+
+#[test]
+fn allowed_libfuncs_of_unknown_major_version() {
+    let file = File::open("tests/data/sierra_contract/sierra_1_9_0.json").unwrap();
+    let mut sierra_json: serde_json::Value = serde_json::from_reader(file).unwrap();
+    sierra_json["sierra_program"][0] = "0x2".into();
+
+    let list = AllowedLibfuncsList::Name("all".to_string());
+    let error = find_disallowed_libfuncs(&sierra_json, &list).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Validating allowed libfuncs is not supported for Sierra version 2.9.0"
+    );
+}
+
+#[test]
+fn default_allowed_libfuncs_list() {
+    let file = File::open("tests/data/sierra_contract/sierra_1_7_0_trace_hint.json").unwrap();
+    let sierra_json: serde_json::Value = serde_json::from_reader(file).unwrap();
+
+    let error =
+        compile_contract_with_options(sierra_json.clone(), &CompileContractOptions::default())
+            .unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("Contract uses libfuncs that are not on the `audited` allowed libfuncs list"));
+
+    let options = CompileContractOptions {
+        skip_allowed_libfuncs_check: true,
+        ..CompileContractOptions::default()
+    };
+    assert!(compile_contract_with_options(sierra_json, &options).is_ok());
+}
+
+#[cfg(not(feature = "sierra-0-1"))]
+#[test]
+fn backend_not_compiled_in() {
+    let file = File::open("tests/data/sierra_contract/sierra_0_1_0.json").unwrap();
+    let sierra_json = serde_json::from_reader(file).unwrap();
+
+    let error = compile_contract(sierra_json).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("rebuild USC with the `sierra-0-1` feature enabled"));
+}
+
+struct StubBackend;
+
+impl SierraBackend for StubBackend {
+    fn name(&self) -> &str {
+        "stub"
+    }
+
+    fn cairo_lang_version(&self) -> &str {
+        "0.0.0"
+    }
+
+    fn supported_sierra_versions(&self) -> RangeInclusive<(u8, u8)> {
+        (1, 9)..=(1, 10)
+    }
+
+    fn compile_contract(
+        &self,
+        _sierra_json: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Ok(serde_json::json!({"compiled_by": "stub"}))
+    }
+}
+
+#[test]
+fn registered_backend() {
+    let file = File::open("tests/data/sierra_contract/sierra_1_9_0.json").unwrap();
+    let sierra_json: serde_json::Value = serde_json::from_reader(file).unwrap();
+    let mut registry = BackendRegistry::default();
+    registry.register(StubBackend);
+
+    let casm = compile_contract_with_registry(sierra_json.clone(), &registry, false).unwrap();
+    assert_eq!(casm["compiled_by"], "stub");
+    assert_eq!(registry.max_supported_sierra_version(), Some((1, 10)));
+
+    let casm = compile_contract_with_registry(sierra_json, &BackendRegistry::new(), true);
+    assert!(casm.is_err());
+}
+
+struct PanickingBackend;
+
+impl SierraBackend for PanickingBackend {
+    fn name(&self) -> &str {
+        "panicking"
+    }
+
+    fn cairo_lang_version(&self) -> &str {
+        "0.0.0"
+    }
+
+    fn supported_sierra_versions(&self) -> RangeInclusive<(u8, u8)> {
+        (1, 9)..=(1, 9)
+    }
+
+    fn compile_contract(
+        &self,
+        _sierra_json: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        panic!("unsupported libfunc");
+    }
+}
+
+#[test]
+fn backend_panic() {
+    let file = File::open("tests/data/sierra_contract/sierra_1_9_0.json").unwrap();
+    let sierra_json: serde_json::Value = serde_json::from_reader(file).unwrap();
+    let mut registry = BackendRegistry::new();
+    registry.register(PanickingBackend);
+
+    let error = compile_contract_with_registry(sierra_json.clone(), &registry, false).unwrap_err();
+    let backend_panic = error.downcast_ref::<BackendPanic>().unwrap();
+    assert_eq!(backend_panic.backend, "panicking");
+    assert_eq!(backend_panic.sierra_version.as_deref(), Some("1.9.0"));
+    assert_eq!(backend_panic.message, "unsupported libfunc");
+    assert_eq!(
+        error.to_string(),
+        "panicking 0.0.0 panicked on Sierra version 1.9.0: unsupported libfunc"
+    );
+
+    assert!(compile_contract(sierra_json).is_ok());
+}
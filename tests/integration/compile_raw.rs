@@ -1,7 +1,9 @@
 use cairo_lang_sierra::program::Program;
 use std::fs::File;
 use test_case::test_case;
-use universal_sierra_compiler::compile_raw;
+use universal_sierra_compiler::{
+    compile_raw, compile_raw_with_options, deserialize_sierra_program, CompileRawOptions,
+};
 
 #[test_case("1_9_0"; "sierra 1.9.0")]
 #[test_case("1_8_0"; "sierra 1.8.0")]
@@ -27,3 +29,94 @@ fn compile_raw_sierra(sierra_version: &str) {
             .is_some_and(serde_json::Value::is_object)
     }));
 }
+
+#[test]
+fn functions_with_debug_names() {
+    let file = File::open("tests/data/sierra_raw/sierra_1_9_0.json").unwrap();
+    let artifact: Program = serde_json::from_reader(file).unwrap();
+    let compiled = compile_raw(&artifact).unwrap();
+    let functions = compiled["functions"].as_array().unwrap();
+    let function_costs = compiled["function_costs"].as_object().unwrap();
+
+    assert_eq!(functions.len(), artifact.funcs.len());
+    for (function, sierra_function) in functions.iter().zip(&artifact.funcs) {
+        let entry_point = sierra_function.entry_point.0;
+
+        assert_eq!(function["id"].as_u64().unwrap(), sierra_function.id.id);
+        assert_eq!(
+            function["name"].as_str(),
+            sierra_function.id.debug_name.as_deref()
+        );
+        assert_eq!(
+            function["entry_point"].as_u64().unwrap(),
+            entry_point as u64
+        );
+        assert_eq!(
+            function["casm_offset"].as_u64().unwrap(),
+            compiled["debug_info"][entry_point][0].as_u64().unwrap()
+        );
+        assert_eq!(function["costs"], function_costs[&entry_point.to_string()]);
+    }
+}
+
+#[test]
+fn no_functions_without_debug_names() {
+    let file = File::open("tests/data/sierra_raw/sierra_1_9_0.json").unwrap();
+    let mut artifact: Program = serde_json::from_reader(file).unwrap();
+    for function in &mut artifact.funcs {
+        function.id.debug_name = None;
+    }
+    let compiled = compile_raw(&artifact).unwrap();
+
+    assert!(compiled.get("functions").is_none());
+    assert!(compiled["function_costs"].is_object());
+}
+
+#[test]
+fn statement_metadata() {
+    let file = File::open("tests/data/sierra_raw/sierra_1_9_0.json").unwrap();
+    let artifact: Program = serde_json::from_reader(file).unwrap();
+    let options = CompileRawOptions {
+        statement_metadata: true,
+    };
+    let compiled = compile_raw_with_options(&artifact, &options).unwrap();
+    let statements = compiled["statement_metadata"].as_array().unwrap();
+
+    assert!(!statements.is_empty());
+    assert!(statements
+        .windows(2)
+        .all(|pair| pair[0]["statement_idx"].as_u64() < pair[1]["statement_idx"].as_u64()));
+    assert!(statements.iter().all(|statement| {
+        statement["statement_idx"].as_u64().unwrap() < artifact.statements.len() as u64
+            && statement["gas"].is_object()
+            && (statement["ap_change"].is_null() || statement["ap_change"].is_u64())
+    }));
+    assert!(statements
+        .iter()
+        .any(|statement| !statement["gas"].as_object().unwrap().is_empty()));
+
+    let default = compile_raw(&artifact).unwrap();
+    assert!(default.get("statement_metadata").is_none());
+}
+
+#[test]
+fn versioned_program_debug_info() {
+    let file = File::open("tests/data/sierra_raw/sierra_1_9_0.json").unwrap();
+    let mut sierra_json: serde_json::Value = serde_json::from_reader(file).unwrap();
+    for function in sierra_json["funcs"].as_array_mut().unwrap() {
+        function["id"]["debug_name"] = serde_json::Value::Null;
+    }
+
+    let program = deserialize_sierra_program(sierra_json.clone()).unwrap();
+    assert!(program
+        .funcs
+        .iter()
+        .all(|function| function.id.debug_name.is_some()));
+
+    sierra_json["version"] = serde_json::json!(2);
+    let error = deserialize_sierra_program(sierra_json).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unsupported version of the Sierra program artifact: 2"
+    );
+}